@@ -38,6 +38,18 @@ pub fn call(s: Structure) -> TokenStream {
     let marker = utils::marker_field(&fields).unwrap_or_else(|| format_ident!("_"));
     let filtered_fields = utils::filter_fields(&fields, &marker);
     let args = utils::fields_to_args(&filtered_fields);
+    let arg_tys = bindings
+        .iter()
+        .zip(&fields)
+        .filter(|(_, (field, _))| *field != marker)
+        .map(|(bi, (_, ty))| {
+            let ty = quote!(#ty).to_string();
+            if is_compact(&bi.ast().attrs) {
+                format!("Compact<{}>", ty)
+            } else {
+                ty
+            }
+        });
     let build_struct = utils::build_struct(ident, &fields);
     let call_trait = format_ident!("{}CallExt", call_name.to_camel_case());
     let call = format_ident!("{}", call_name);
@@ -49,6 +61,14 @@ pub fn call(s: Structure) -> TokenStream {
             const FUNCTION: &'static str = #call_name;
         }
 
+        impl#generics #subxt::Validate<T> for #ident<#(#params),*> {
+            fn validate(
+                metadata: &#subxt::Metadata,
+            ) -> Result<(), #subxt::MetadataError> {
+                metadata.validate_call(MODULE, #call_name, &[#(#arg_tys),*])
+            }
+        }
+
         /// Call extension trait.
         #[async_trait::async_trait]
         pub trait #call_trait<T: #subxt::Runtime + #module> {
@@ -93,6 +113,24 @@ pub fn call(s: Structure) -> TokenStream {
     }
 }
 
+/// Returns `true` if the field has a `#[codec(compact)]` attribute, i.e. the metadata names
+/// its type `Compact<_>`.
+fn is_compact(attrs: &[syn::Attribute]) -> bool {
+    attrs.iter().filter(|attr| attr.path.is_ident("codec")).any(|attr| {
+        match attr.parse_meta() {
+            Ok(syn::Meta::List(list)) => {
+                list.nested.iter().any(|nested| {
+                    matches!(
+                        nested,
+                        syn::NestedMeta::Meta(syn::Meta::Path(path)) if path.is_ident("compact")
+                    )
+                })
+            }
+            _ => false,
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -107,12 +145,22 @@ mod tests {
                 pub amount: T::Balance,
             }
         };
+        let address = quote!(&'a <T as System>::Address).to_string();
+        let amount = format!("Compact<{}>", quote!(T::Balance));
         let expected = quote! {
             impl<'a, T: Balances> substrate_subxt::Call<T> for TransferCall<'a, T> {
                 const MODULE: &'static str = MODULE;
                 const FUNCTION: &'static str = "transfer";
             }
 
+            impl<'a, T: Balances> substrate_subxt::Validate<T> for TransferCall<'a, T> {
+                fn validate(
+                    metadata: &substrate_subxt::Metadata,
+                ) -> Result<(), substrate_subxt::MetadataError> {
+                    metadata.validate_call(MODULE, "transfer", &[#address, #amount])
+                }
+            }
+
             /// Call extension trait.
             #[async_trait::async_trait]
             pub trait TransferCallExt<T: substrate_subxt::Runtime + Balances> {
@@ -35,6 +35,13 @@ pub fn event(s: Structure) -> TokenStream {
     let event_name = utils::ident_to_name(ident, "Event").to_camel_case();
     let event = format_ident!("{}", event_name.to_snake_case());
     let event_trait = format_ident!("{}EventExt", event_name);
+    let bindings = utils::bindings(&s);
+    let fields = utils::fields(&bindings);
+    let args = match utils::marker_field(&fields) {
+        Some(marker) => utils::filter_fields(&fields, &marker),
+        None => fields,
+    };
+    let arg_tys = args.iter().map(|(_, ty)| quote!(#ty).to_string());
 
     quote! {
         impl<T: #module> #subxt::Event<T> for #ident<T> {
@@ -42,6 +49,23 @@ pub fn event(s: Structure) -> TokenStream {
             const EVENT: &'static str = #event_name;
         }
 
+        impl<T: #module> #subxt::RuntimeEvent<T> for #ident<T> {
+            fn decode_raw(raw: &#subxt::RawEvent) -> Result<Option<Self>, #codec::Error> {
+                if raw.module == MODULE && raw.variant == #event_name {
+                    return Ok(Some(#codec::Decode::decode(&mut &raw.data[..])?))
+                }
+                Ok(None)
+            }
+        }
+
+        impl<T: #module> #subxt::Validate<T> for #ident<T> {
+            fn validate(
+                metadata: &#subxt::Metadata,
+            ) -> Result<(), #subxt::MetadataError> {
+                metadata.validate_event(MODULE, #event_name, &[#(#arg_tys),*])
+            }
+        }
+
         /// Event extension trait.
         pub trait #event_trait<T: #module> {
             /// Retrieves the event.
@@ -70,12 +94,35 @@ mod tests {
                 pub amount: T::Balance,
             }
         };
+        let account_id = quote!(<T as System>::AccountId).to_string();
+        let balance = quote!(T::Balance).to_string();
         let expected = quote! {
             impl<T: Balances> substrate_subxt::Event<T> for TransferEvent<T> {
                 const MODULE: &'static str = MODULE;
                 const EVENT: &'static str = "Transfer";
             }
 
+            impl<T: Balances> substrate_subxt::RuntimeEvent<T> for TransferEvent<T> {
+                fn decode_raw(raw: &substrate_subxt::RawEvent) -> Result<Option<Self>, codec::Error> {
+                    if raw.module == MODULE && raw.variant == "Transfer" {
+                        return Ok(Some(codec::Decode::decode(&mut &raw.data[..])?))
+                    }
+                    Ok(None)
+                }
+            }
+
+            impl<T: Balances> substrate_subxt::Validate<T> for TransferEvent<T> {
+                fn validate(
+                    metadata: &substrate_subxt::Metadata,
+                ) -> Result<(), substrate_subxt::MetadataError> {
+                    metadata.validate_event(
+                        MODULE,
+                        "Transfer",
+                        &[#account_id, #account_id, #balance]
+                    )
+                }
+            }
+
             /// Event extension trait.
             pub trait TransferEventExt<T: Balances> {
                 /// Retrieves the event.
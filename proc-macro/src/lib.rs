@@ -17,8 +17,10 @@
 extern crate proc_macro;
 
 mod call;
+mod constant;
 mod event;
 mod module;
+mod runtime_event;
 mod store;
 mod test;
 mod utils;
@@ -38,6 +40,13 @@ use synstructure::{
 ///
 /// If you do not want an associated type to be registered, likely because you never expect it as part of a response payload to be decoded, use `#[module(ignore)]` on the type.
 ///
+/// The `Call`, `Event` and `Store` definitions of the module can be listed in the arguments, e.g.
+/// `#[module(calls(TransferCall), events(TransferEvent), stores(AccountStore))]`, to register
+/// them for `Client::validate_modules`. The listed events also generate a `<Module>Event` enum
+/// implementing [RuntimeEvent](../substrate_subxt/trait.RuntimeEvent.html), with a variant per
+/// event, e.g. `BalancesEvent::Transfer(TransferEvent<T>)`. The events must implement `Clone`,
+/// `Debug`, `Eq` and `PartialEq`.
+///
 /// Example:
 ///
 /// ```ignore
@@ -152,6 +161,60 @@ fn store(s: Structure) -> TokenStream {
     store::store(s).into()
 }
 
+decl_derive!(
+    [Constant, attributes(constant)] =>
+    /// Derive macro that implements [substrate_subxt::Constant](../substrate_subxt/trait.Constant.html)
+    /// for your struct and adds an extension trait for retrieving the constant from the metadata.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// #[derive(Constant)]
+    /// pub struct ExistentialDepositConstant<T: Balances> {
+    ///     #[constant(returns = T::Balance)]
+    ///     /// Runtime marker.
+    ///     pub _runtime: PhantomData<T>,
+    /// }
+    /// ```
+    ///
+    /// The name of the constant is the struct name without the `Constant` suffix, and the
+    /// `ExistentialDepositConstantExt` trait provides `client.existential_deposit()`.
+
+    #[proc_macro_error] constant
+);
+fn constant(s: Structure) -> TokenStream {
+    constant::constant(s).into()
+}
+
+decl_derive!(
+    [RuntimeEvent] =>
+    /// Derive macro that implements [substrate_subxt::RuntimeEvent](../substrate_subxt/trait.RuntimeEvent.html)
+    /// for an enum with one variant per module, so that raw events can be consumed with an
+    /// exhaustive `match`.
+    ///
+    /// Each variant holds the `<Module>Event` enum generated by the [#module](../substrate_subxt/attr.module.html)
+    /// macro from its `events(..)` argument, or a single [Event]() type.
+    ///
+    /// Example:
+    /// ```rust,ignore
+    /// #[derive(Debug, RuntimeEvent)]
+    /// pub enum NodeEvent<T: Balances> {
+    ///     System(SystemEvent<T>),
+    ///     Balances(BalancesEvent<T>),
+    /// }
+    ///
+    /// match NodeEvent::<MyRuntime>::decode_raw(&raw)? {
+    ///     Some(NodeEvent::Balances(BalancesEvent::Transfer(transfer))) => { /* … */ }
+    ///     Some(NodeEvent::System(_)) => { /* … */ }
+    ///     None => { /* event of a module or event not part of the enum */ }
+    /// }
+    /// ```
+
+    #[proc_macro_error] runtime_event
+);
+fn runtime_event(s: Structure) -> TokenStream {
+    runtime_event::runtime_event(s).into()
+}
+
 #[proc_macro]
 #[proc_macro_error]
 pub fn subxt_test(input: TokenStream) -> TokenStream {
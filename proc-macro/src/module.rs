@@ -15,22 +15,31 @@
 // along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
 
 use crate::utils;
-use heck::SnakeCase;
+use heck::{
+    CamelCase,
+    SnakeCase,
+};
 use proc_macro2::TokenStream;
 use proc_macro_error::abort;
 use quote::{
     format_ident,
     quote,
 };
-use syn::parse::{
-    Parse,
-    ParseStream,
+use syn::{
+    parse::{
+        Parse,
+        ParseStream,
+    },
+    punctuated::Punctuated,
 };
 
 mod kw {
     use syn::custom_keyword;
 
     custom_keyword!(ignore);
+    custom_keyword!(calls);
+    custom_keyword!(events);
+    custom_keyword!(stores);
 }
 
 #[derive(Debug)]
@@ -46,6 +55,63 @@ impl Parse for ModuleAttr {
 
 type ModuleAttrs = utils::Attrs<ModuleAttr>;
 
+/// The `Call`, `Event` and `Store` definitions of a module, listed in the `#[module]`
+/// arguments:
+///
+/// ```ignore
+/// #[module(calls(TransferCall), events(TransferEvent), stores(AccountStore))]
+/// pub trait Balances: System {}
+/// ```
+///
+/// The whole attribute, e.g. `#[module(calls(TransferCall))]`, is accepted as well.
+#[derive(Debug, Default)]
+struct ModuleArgs {
+    calls: Vec<syn::Ident>,
+    events: Vec<syn::Ident>,
+    stores: Vec<syn::Ident>,
+}
+
+impl Parse for ModuleArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(syn::Token![#]) {
+            // the whole `#[module(..)]` attribute
+            let attrs = input.call(syn::Attribute::parse_outer)?;
+            return match &attrs[..] {
+                [attr] if attr.path.is_ident("module") && attr.tokens.is_empty() => {
+                    Ok(Self::default())
+                }
+                [attr] if attr.path.is_ident("module") => attr.parse_args(),
+                _ => Err(input.error("expected a single `#[module]` attribute")),
+            }
+        }
+        let mut args = Self::default();
+        while !input.is_empty() {
+            let lookahead = input.lookahead1();
+            let list = if lookahead.peek(kw::calls) {
+                input.parse::<kw::calls>()?;
+                &mut args.calls
+            } else if lookahead.peek(kw::events) {
+                input.parse::<kw::events>()?;
+                &mut args.events
+            } else if lookahead.peek(kw::stores) {
+                input.parse::<kw::stores>()?;
+                &mut args.stores
+            } else {
+                return Err(lookahead.error())
+            };
+            let content;
+            syn::parenthesized!(content in input);
+            let idents: Punctuated<syn::Ident, syn::Token![,]> =
+                content.parse_terminated(syn::Ident::parse)?;
+            list.extend(idents);
+            if !input.is_empty() {
+                input.parse::<syn::Token![,]>()?;
+            }
+        }
+        Ok(args)
+    }
+}
+
 fn ignore(attrs: &[syn::Attribute]) -> bool {
     for attr in attrs {
         if let Some(ident) = attr.path.get_ident() {
@@ -66,6 +132,10 @@ fn event_type_registry_trait_name(module: &syn::Ident) -> syn::Ident {
     format_ident!("{}EventTypeRegistry", module.to_string())
 }
 
+fn module_event_enum_name(module: &syn::Ident) -> syn::Ident {
+    format_ident!("{}Event", module.to_string())
+}
+
 fn with_module_ident(module: &syn::Ident) -> syn::Ident {
     format_ident!("with_{}", module.to_string().to_snake_case())
 }
@@ -106,8 +176,9 @@ fn parse_event_type_attr(attr: &syn::Attribute) -> Option<(String, syn::Type)> {
     }
 }
 
-/// Attribute macro that registers the type sizes used by the module; also sets the `MODULE` constant.
-pub fn module(_args: TokenStream, tokens: TokenStream) -> TokenStream {
+/// Attribute macro that registers the type sizes and definitions of the module; also sets
+/// the `MODULE` constant.
+pub fn module(args: TokenStream, tokens: TokenStream) -> TokenStream {
     let input: Result<syn::ItemTrait, _> = syn::parse2(tokens.clone());
     let mut input = if let Ok(input) = input {
         input
@@ -115,6 +186,7 @@ pub fn module(_args: TokenStream, tokens: TokenStream) -> TokenStream {
         // handle #[module(ignore)] by just returning the tokens
         return tokens
     };
+    let args: ModuleArgs = syn::parse2(args).map_err(|err| abort!("{}", err)).unwrap();
 
     // Parse the inner attributes `event_type` and `event_alias` and remove them from the macro
     // outputs.
@@ -151,6 +223,12 @@ pub fn module(_args: TokenStream, tokens: TokenStream) -> TokenStream {
             self.register_type_size::<#ty>(#ident_str);
         }
     });
+    let definitions = args.calls.iter().chain(&args.events).chain(&args.stores);
+    let module_event_enum = if args.events.is_empty() {
+        quote!()
+    } else {
+        module_event_enum(module, &args.events)
+    };
 
     quote! {
         #input
@@ -169,6 +247,54 @@ pub fn module(_args: TokenStream, tokens: TokenStream) -> TokenStream {
             fn #with_module(&mut self) {
                 #(#associated_types)*
                 #(#types)*
+                #(self.register_validator::<#definitions<T>>();)*
+            }
+        }
+
+        #module_event_enum
+    }
+}
+
+/// Generates an enum with a variant per event of the module, implementing `RuntimeEvent`.
+fn module_event_enum(module: &syn::Ident, events: &[syn::Ident]) -> TokenStream {
+    let subxt = utils::use_crate("substrate-subxt");
+    let codec = utils::use_crate("parity-scale-codec");
+    let module_event = module_event_enum_name(module);
+    let doc = format!(" The events of the `{}` module.", module);
+    let variants = events
+        .iter()
+        .map(|event| {
+            let name =
+                format_ident!("{}", utils::ident_to_name(event, "Event").to_camel_case());
+            let doc = format!(" The `{}` event.", name);
+            (name, doc)
+        })
+        .collect::<Vec<_>>();
+    let variant_defs = variants.iter().zip(events).map(|((name, doc), event)| {
+        quote! {
+            #[doc = #doc]
+            #name(#event<T>),
+        }
+    });
+    let decode_variants = variants.iter().zip(events).map(|((name, _), event)| {
+        quote! {
+            if let Some(event) = <#event<T> as #subxt::RuntimeEvent<T>>::decode_raw(raw)? {
+                return Ok(Some(Self::#name(event)))
+            }
+        }
+    });
+
+    quote! {
+        #[doc = #doc]
+        #[derive(Clone, Debug, Eq, PartialEq)]
+        pub enum #module_event<T: #module> {
+            #(#variant_defs)*
+        }
+
+        impl<T: #module> #subxt::RuntimeEvent<T> for #module_event<T> {
+            fn decode_raw(raw: &#subxt::RawEvent) -> Result<Option<Self>, #codec::Error> {
+                #(#decode_variants)*
+                Ok(None)
             }
         }
     }
@@ -268,4 +394,90 @@ mod tests {
         let result = module(attr, input);
         utils::assert_proc_macro(result, expected);
     }
+
+    #[test]
+    fn test_module_args() {
+        let names = |idents: &[syn::Ident]| {
+            idents.iter().map(ToString::to_string).collect::<Vec<_>>()
+        };
+
+        for attr in &[quote!(), quote!(#[module])] {
+            let args: ModuleArgs = syn::parse2(attr.clone()).unwrap();
+            assert!(args.calls.is_empty());
+            assert!(args.events.is_empty());
+            assert!(args.stores.is_empty());
+        }
+
+        for attr in &[
+            quote!(stores(AccountStore), calls(TransferCall, SetCodeCall,),),
+            quote!(#[module(calls(TransferCall), stores(AccountStore), calls(SetCodeCall))]),
+        ] {
+            let args: ModuleArgs = syn::parse2(attr.clone()).unwrap();
+            assert_eq!(names(&args.calls), vec!["TransferCall", "SetCodeCall"]);
+            assert!(args.events.is_empty());
+            assert_eq!(names(&args.stores), vec!["AccountStore"]);
+        }
+
+        assert!(syn::parse2::<ModuleArgs>(quote!(errors(InsufficientBalance))).is_err());
+        assert!(syn::parse2::<ModuleArgs>(quote!(calls TransferCall)).is_err());
+        assert!(syn::parse2::<ModuleArgs>(quote!(calls(TransferCall) events())).is_err());
+        assert!(syn::parse2::<ModuleArgs>(quote!(#[event(TransferEvent)])).is_err());
+    }
+
+    #[test]
+    fn test_module_definitions() {
+        let attr = quote!(
+            calls(TransferCall),
+            events(TransferEvent),
+            stores(AccountStore)
+        );
+        let input = quote! {
+            pub trait Balances: System {
+                type Balance: Parameter;
+            }
+        };
+        let expected = quote! {
+            pub trait Balances: System {
+                type Balance: Parameter;
+            }
+
+            const MODULE: &str = "Balances";
+
+            /// `EventTypeRegistry` extension trait.
+            pub trait BalancesEventTypeRegistry {
+                /// Registers this modules types.
+                fn with_balances(&mut self);
+            }
+
+            impl<T: Balances + substrate_subxt::Runtime> BalancesEventTypeRegistry for
+                substrate_subxt::EventTypeRegistry<T>
+            {
+                fn with_balances(&mut self) {
+                    self.register_type_size::<T::Balance>("Balance");
+                    self.register_validator::<TransferCall<T>>();
+                    self.register_validator::<TransferEvent<T>>();
+                    self.register_validator::<AccountStore<T>>();
+                }
+            }
+
+            /// The events of the `Balances` module.
+            #[derive(Clone, Debug, Eq, PartialEq)]
+            pub enum BalancesEvent<T: Balances> {
+                /// The `Transfer` event.
+                Transfer(TransferEvent<T>),
+            }
+
+            impl<T: Balances> substrate_subxt::RuntimeEvent<T> for BalancesEvent<T> {
+                fn decode_raw(raw: &substrate_subxt::RawEvent) -> Result<Option<Self>, codec::Error> {
+                    if let Some(event) = <TransferEvent<T> as substrate_subxt::RuntimeEvent<T>>::decode_raw(raw)? {
+                        return Ok(Some(Self::Transfer(event)))
+                    }
+                    Ok(None)
+                }
+            }
+        };
+
+        let result = module(attr, input);
+        utils::assert_proc_macro(result, expected);
+    }
 }
@@ -0,0 +1,83 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::utils;
+use proc_macro2::TokenStream;
+use proc_macro_error::abort;
+use quote::quote;
+use synstructure::Structure;
+
+pub fn runtime_event(s: Structure) -> TokenStream {
+    let subxt = utils::use_crate("substrate-subxt");
+    let codec = utils::use_crate("parity-scale-codec");
+    let ident = &s.ast().ident;
+    let generics = &s.ast().generics;
+    let params = utils::type_params(generics);
+    let variants = s.variants().iter().map(|variant| {
+        let name = variant.ast().ident;
+        let ty = match variant.bindings() {
+            [binding] => &binding.ast().ty,
+            _ => abort!(name, "Expected a single event or module event enum field"),
+        };
+        quote! {
+            if let Some(event) = <#ty as #subxt::RuntimeEvent<T>>::decode_raw(raw)? {
+                return Ok(Some(Self::#name(event)))
+            }
+        }
+    });
+
+    quote! {
+        impl#generics #subxt::RuntimeEvent<T> for #ident<#(#params),*> {
+            fn decode_raw(raw: &#subxt::RawEvent) -> Result<Option<Self>, #codec::Error> {
+                #(#variants)*
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_runtime_event() {
+        let input = quote! {
+            #[derive(Debug, RuntimeEvent)]
+            pub enum NodeEvent<T: Balances> {
+                System(SystemEvent<T>),
+                Transfer(TransferEvent<T>),
+            }
+        };
+        let expected = quote! {
+            impl<T: Balances> substrate_subxt::RuntimeEvent<T> for NodeEvent<T> {
+                fn decode_raw(raw: &substrate_subxt::RawEvent) -> Result<Option<Self>, codec::Error> {
+                    if let Some(event) = <SystemEvent<T> as substrate_subxt::RuntimeEvent<T>>::decode_raw(raw)? {
+                        return Ok(Some(Self::System(event)))
+                    }
+                    if let Some(event) = <TransferEvent<T> as substrate_subxt::RuntimeEvent<T>>::decode_raw(raw)? {
+                        return Ok(Some(Self::Transfer(event)))
+                    }
+                    Ok(None)
+                }
+            }
+        };
+        let derive_input = syn::parse2(input).unwrap();
+        let s = Structure::new(&derive_input);
+        let result = runtime_event(s);
+        utils::assert_proc_macro(result, expected);
+    }
+}
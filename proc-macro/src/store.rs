@@ -80,6 +80,9 @@ pub fn store(s: Structure) -> TokenStream {
     let marker = utils::marker_field(&fields).unwrap_or_else(|| format_ident!("_"));
     let filtered_fields = utils::filter_fields(&fields, &marker);
     let args = utils::fields_to_args(&filtered_fields);
+    let key_tys = filtered_fields
+        .iter()
+        .map(|(_, ty)| quote!(#ty).to_string());
     let build_struct = utils::build_struct(ident, &fields);
     let (ret, store_ret, uses_default) = bindings
         .iter()
@@ -88,6 +91,7 @@ pub fn store(s: Structure) -> TokenStream {
         .unwrap_or_else(|| {
             abort!(ident, "#[store(returns = ..)] needs to be specified.")
         });
+    let value_ty = quote!(#store_ret).to_string();
     let fetch = if uses_default {
         quote!(fetch_or_default)
     } else {
@@ -140,6 +144,19 @@ pub fn store(s: Structure) -> TokenStream {
             }
         }
 
+        impl#generics #subxt::Validate<T> for #ident<#(#params),*> {
+            fn validate(
+                metadata: &#subxt::Metadata,
+            ) -> Result<(), #subxt::MetadataError> {
+                metadata.validate_storage(
+                    MODULE,
+                    #store_name,
+                    &[#(#key_tys),*],
+                    #value_ty,
+                )
+            }
+        }
+
         /// Store extension trait.
         #[async_trait::async_trait]
         pub trait #store_trait<T: #subxt::Runtime + #module> {
@@ -191,6 +208,8 @@ mod tests {
                 account_id: &'a <T as System>::AccountId,
             }
         };
+        let account_id = quote!(&'a <T as System>::AccountId).to_string();
+        let account_data = quote!(AccountData<T::Balance>).to_string();
         let expected = quote! {
             impl<'a, T: Balances> substrate_subxt::Store<T> for AccountStore<'a, T> {
                 const MODULE: &'static str = MODULE;
@@ -218,6 +237,19 @@ mod tests {
                 }
             }
 
+            impl<'a, T: Balances> substrate_subxt::Validate<T> for AccountStore<'a, T> {
+                fn validate(
+                    metadata: &substrate_subxt::Metadata,
+                ) -> Result<(), substrate_subxt::MetadataError> {
+                    metadata.validate_storage(
+                        MODULE,
+                        "Account",
+                        &[#account_id],
+                        #account_data,
+                    )
+                }
+            }
+
             /// Store extension trait.
             #[async_trait::async_trait]
             pub trait AccountStoreExt<T: substrate_subxt::Runtime + Balances> {
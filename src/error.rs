@@ -39,6 +39,9 @@ pub enum Error {
     /// Rpc error.
     #[error("Rpc error: {0}")]
     Rpc(#[from] RequestError),
+    /// Request timeout.
+    #[error("Request {0} timed out")]
+    Timeout(String),
     /// Serde serialization error
     #[error("Serde json error: {0}")]
     Serialization(#[from] serde_json::error::Error),
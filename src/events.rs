@@ -47,9 +47,19 @@ use crate::{
         Error,
         RuntimeError,
     },
+    frame::Validate,
     metadata::{
         EventArg,
         Metadata,
+        MetadataError,
+        ModuleEventMetadata,
+        ModuleWithEvents,
+        StorageMetadata,
+    },
+    value::{
+        DecodedCall,
+        Primitive,
+        Value,
     },
     Phase,
     Runtime,
@@ -58,20 +68,29 @@ use crate::{
 
 /// Raw bytes for an Event
 pub struct RawEvent {
+    /// The phase of the block in which the Event was emitted
+    pub phase: Phase,
     /// The name of the module from whence the Event originated
     pub module: String,
     /// The name of the Event
     pub variant: String,
     /// The raw Event data
     pub data: Vec<u8>,
+    /// The SCALE encoded topics the Event was indexed under
+    pub topics: Vec<Vec<u8>>,
 }
 
 impl std::fmt::Debug for RawEvent {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         f.debug_struct("RawEvent")
+            .field("phase", &self.phase)
             .field("module", &self.module)
             .field("variant", &self.variant)
             .field("data", &hex::encode(&self.data))
+            .field(
+                "topics",
+                &self.topics.iter().map(hex::encode).collect::<Vec<_>>(),
+            )
             .finish()
     }
 }
@@ -79,6 +98,23 @@ impl std::fmt::Debug for RawEvent {
 pub trait TypeSegmenter: DynClone + Send + Sync {
     /// Consumes an object from an input stream, and output the serialized bytes.
     fn segment(&self, input: &mut &[u8], output: &mut Vec<u8>) -> Result<(), Error>;
+    /// Consumes an object from an input stream, and output it as a dynamic [`Value`].
+    ///
+    /// Segmenters which can't decode their type dynamically return an error by default.
+    fn value(&self, _input: &mut &[u8]) -> Result<Value, Error> {
+        Err(Error::Other(
+            "Type segmenter doesn't support dynamic decoding".into(),
+        ))
+    }
+    /// Consumes a `Compact<_>` of the object from an input stream, and output it as a
+    /// dynamic [`Value`].
+    ///
+    /// Segmenters of types without a compact encoding return an error by default.
+    fn compact_value(&self, _input: &mut &[u8]) -> Result<Value, Error> {
+        Err(Error::Other(
+            "Type segmenter doesn't support compact decoding".into(),
+        ))
+    }
 }
 
 // derive object safe Clone impl for `Box<dyn TypeSegmenter>`
@@ -87,12 +123,34 @@ dyn_clone::clone_trait_object!(TypeSegmenter);
 struct TypeMarker<T>(PhantomData<T>);
 impl<T> TypeSegmenter for TypeMarker<T>
 where
-    T: Codec + Send + Sync,
+    T: Codec + Send + Sync + 'static,
 {
     fn segment(&self, input: &mut &[u8], output: &mut Vec<u8>) -> Result<(), Error> {
         T::decode(input).map_err(Error::from)?.encode_to(output);
         Ok(())
     }
+
+    fn value(&self, input: &mut &[u8]) -> Result<Value, Error> {
+        if let Some(primitive) = Primitive::decode_typed::<T>(input) {
+            return Ok(Value::Primitive(primitive?))
+        }
+        // the structure of other types is unknown, so keep their encoded bytes
+        let mut bytes = Vec::new();
+        self.segment(input, &mut bytes)?;
+        Ok(Value::Primitive(Primitive::Bytes(bytes)))
+    }
+
+    fn compact_value(&self, input: &mut &[u8]) -> Result<Value, Error> {
+        match Primitive::decode_compact_typed::<T>(input) {
+            Some(primitive) => Ok(Value::Primitive(primitive?)),
+            None => {
+                Err(Error::Other(format!(
+                    "Type segmenter doesn't support compact decoding of {}",
+                    std::any::type_name::<T>()
+                )))
+            }
+        }
+    }
 }
 
 impl<T> Clone for TypeMarker<T> {
@@ -132,6 +190,11 @@ impl<T: Runtime + System> EventsDecoder<T> {
         }
     }
 
+    /// Returns the event type registry.
+    pub fn event_type_registry(&self) -> &EventTypeRegistry<T> {
+        &self.event_type_registry
+    }
+
     /// Decode events.
     pub fn decode_events(&self, input: &mut &[u8]) -> Result<Vec<(Phase, Raw)>, Error> {
         let compact_len = <Compact<u32>>::decode(input)?;
@@ -140,54 +203,153 @@ impl<T: Runtime + System> EventsDecoder<T> {
         let mut r = Vec::new();
         for _ in 0..len {
             // decode EventRecord
-            let phase = Phase::decode(input)?;
-            let module_variant = input.read_byte()?;
-
-            let module = self.metadata.module_with_events(module_variant)?;
-            let event_variant = input.read_byte()?;
-            let event_metadata = module.event(event_variant)?;
-
-            log::debug!(
-                "received event '{}::{}' ({:?})",
-                module.name(),
-                event_metadata.name,
-                event_metadata.arguments()
-            );
-
-            let mut event_data = Vec::<u8>::new();
-            let mut event_errors = Vec::<RuntimeError>::new();
-            let result = self.decode_raw_bytes(
-                &event_metadata.arguments(),
-                input,
-                &mut event_data,
-                &mut event_errors,
-            );
-            let raw = match result {
-                Ok(()) => {
-                    log::debug!("raw bytes: {}", hex::encode(&event_data),);
-
-                    let event = RawEvent {
+            let (phase, module, event_metadata) = self.decode_event_header(input)?;
+            let raws = self.decode_event(&phase, module, event_metadata, input)?;
+            r.extend(raws.into_iter().map(|raw| (phase.clone(), raw)));
+        }
+        Ok(r)
+    }
+
+    /// Decode events, reporting events that can't be decoded (e.g. because of a missing type
+    /// size) as [`Raw::Undecodable`] instead of failing the whole block.
+    ///
+    /// As the size of an undecodable event is unknown, the start of the next record is found
+    /// by trying each following offset until the remaining records decode, skipping further
+    /// undecodable events the same way, and consume the input exactly. An error is only
+    /// returned if no such offset exists.
+    pub fn decode_events_lenient(
+        &self,
+        input: &mut &[u8],
+    ) -> Result<Vec<(Phase, Raw)>, Error> {
+        let compact_len = <Compact<u32>>::decode(input)?;
+        let records = self.decode_records_lenient(
+            input,
+            compact_len.0 as usize,
+            &mut HashSet::new(),
+        )?;
+        *input = &[];
+        Ok(records)
+    }
+
+    /// Decodes `count` records consuming `input` exactly, skipping undecodable events.
+    ///
+    /// `failed` holds the `(input.len(), count)` of the suffixes known not to decode, so each
+    /// candidate start of a record is only tried once, even after several consecutive
+    /// undecodable events.
+    fn decode_records_lenient(
+        &self,
+        mut input: &[u8],
+        mut count: usize,
+        failed: &mut HashSet<(usize, usize)>,
+    ) -> Result<Vec<(Phase, Raw)>, Error> {
+        let mut records = Vec::new();
+        let mut visited = Vec::new();
+        let result = loop {
+            if count == 0 {
+                if input.is_empty() {
+                    return Ok(records)
+                }
+                break Err("Unexpected bytes after the events".into())
+            }
+            if failed.contains(&(input.len(), count)) {
+                break Err("Undecodable events".into())
+            }
+            visited.push((input.len(), count));
+
+            let (phase, module, event_metadata) =
+                match self.decode_event_header(&mut input) {
+                    Ok(header) => header,
+                    Err(error) => break Err(error),
+                };
+            let data = input;
+            let error =
+                match self.decode_event(&phase, module, event_metadata, &mut input) {
+                    Ok(raws) => {
+                        records.extend(raws.into_iter().map(|raw| (phase.clone(), raw)));
+                        count -= 1;
+                        continue
+                    }
+                    Err(error) => error,
+                };
+            let skipped = (0..=data.len()).find_map(|offset| {
+                let mut next = &data[offset..];
+                Vec::<T::Hash>::decode(&mut next).ok()?;
+                self.decode_records_lenient(next, count - 1, failed).ok()
+            });
+            match skipped {
+                Some(rest) => {
+                    log::warn!(
+                        "skipping undecodable event '{}::{}': {}",
+                        module.name(),
+                        event_metadata.name,
+                        error
+                    );
+                    let raw = Raw::Undecodable {
                         module: module.name().to_string(),
                         variant: event_metadata.name.clone(),
-                        data: event_data,
+                        error,
                     };
-
-                    // topics come after the event data in EventRecord
-                    let _topics = Vec::<T::Hash>::decode(input)?;
-                    Raw::Event(event)
+                    records.push((phase, raw));
+                    records.extend(rest);
+                    return Ok(records)
                 }
-                Err(err) => return Err(err),
-            };
-
-            if event_errors.is_empty() {
-                r.push((phase.clone(), raw));
+                None => break Err(error),
             }
+        };
+        failed.extend(visited);
+        result
+    }
 
-            for err in event_errors {
-                r.push((phase.clone(), Raw::Error(err)));
-            }
+    fn decode_event_header(
+        &self,
+        input: &mut &[u8],
+    ) -> Result<(Phase, &ModuleWithEvents, &ModuleEventMetadata), Error> {
+        let phase = Phase::decode(input)?;
+        let module_variant = input.read_byte()?;
+
+        let module = self.metadata.module_with_events(module_variant)?;
+        let event_variant = input.read_byte()?;
+        let event_metadata = module.event(event_variant)?;
+        Ok((phase, module, event_metadata))
+    }
+
+    fn decode_event(
+        &self,
+        phase: &Phase,
+        module: &ModuleWithEvents,
+        event_metadata: &ModuleEventMetadata,
+        input: &mut &[u8],
+    ) -> Result<Vec<Raw>, Error> {
+        log::debug!(
+            "received event '{}::{}' ({:?})",
+            module.name(),
+            event_metadata.name,
+            event_metadata.arguments()
+        );
+
+        let mut event_data = Vec::<u8>::new();
+        let mut event_errors = Vec::<RuntimeError>::new();
+        self.decode_raw_bytes(
+            &event_metadata.arguments(),
+            input,
+            &mut event_data,
+            &mut event_errors,
+        )?;
+        log::debug!("raw bytes: {}", hex::encode(&event_data),);
+
+        // topics come after the event data in EventRecord
+        let topics = Vec::<T::Hash>::decode(input)?;
+
+        if !event_errors.is_empty() {
+            return Ok(event_errors.into_iter().map(Raw::Error).collect())
         }
-        Ok(r)
+        Ok(vec![Raw::Event(RawEvent {
+            phase: phase.clone(),
+            module: module.name().to_string(),
+            variant: event_metadata.name.clone(),
+            data: event_data,
+            topics: topics.iter().map(Encode::encode).collect(),
+        })])
     }
 
     fn decode_raw_bytes<W: Output>(
@@ -248,12 +410,207 @@ impl<T: Runtime + System> EventsDecoder<T> {
         }
         Ok(())
     }
+
+    /// Decodes the data of a raw event into a dynamic [`Value::Composite`] of its arguments.
+    pub fn decode_event_value(&self, event: &RawEvent) -> Result<Value, Error> {
+        let event_metadata = self
+            .metadata
+            .module_with_events_by_name(&event.module)?
+            .event_by_name(&event.variant)?;
+        let values =
+            self.decode_values(&event_metadata.arguments(), &mut &event.data[..], true)?;
+        Ok(Value::Composite(values))
+    }
+
+    /// Decodes an encoded call, e.g. of an extrinsic, into its named argument values.
+    pub fn decode_call(&self, input: &mut &[u8]) -> Result<DecodedCall, Error> {
+        let module = self
+            .metadata
+            .module_with_calls_by_index(input.read_byte()?)?;
+        let call = module.call_by_index(input.read_byte()?)?;
+        let mut arguments = Vec::new();
+        for arg in call.arguments() {
+            let ty = arg.ty.parse::<EventArg>().map_err(MetadataError::from)?;
+            arguments.push((arg.name.clone(), self.decode_value(&ty, input)?));
+        }
+        Ok(DecodedCall {
+            module: module.name().to_string(),
+            function: call.name().clone(),
+            arguments,
+        })
+    }
+
+    /// Decodes a storage value into a dynamic [`Value`] using its type from the metadata.
+    pub fn decode_storage_value(
+        &self,
+        storage: &StorageMetadata,
+        data: &[u8],
+    ) -> Result<Value, Error> {
+        let ty = storage
+            .value_ty()
+            .parse::<EventArg>()
+            .map_err(MetadataError::from)?;
+        self.decode_value(&ty, &mut &data[..])
+    }
+
+    /// Decodes a value of the type described by `arg` into a dynamic [`Value`].
+    pub fn decode_value(
+        &self,
+        arg: &EventArg,
+        input: &mut &[u8],
+    ) -> Result<Value, Error> {
+        self.decode_arg(arg, input, false)
+    }
+
+    /// Decodes a value of the type described by `arg`.
+    ///
+    /// `event_data` is set when decoding [`RawEvent::data`], which doesn't contain the
+    /// `DispatchResult` and `DispatchError` arguments of the event.
+    fn decode_arg(
+        &self,
+        arg: &EventArg,
+        input: &mut &[u8],
+        event_data: bool,
+    ) -> Result<Value, Error> {
+        match arg {
+            EventArg::Vec(arg) => {
+                let len = <Compact<u32>>::decode(input)?.0 as usize;
+                if **arg == EventArg::Primitive("u8".into()) {
+                    if len > input.len() {
+                        return Err(
+                            codec::Error::from("Not enough data to fill buffer").into()
+                        )
+                    }
+                    let mut bytes = vec![0u8; len];
+                    input.read(&mut bytes)?;
+                    return Ok(Value::Primitive(Primitive::Bytes(bytes)))
+                }
+                // the length is untrusted, so don't allocate more than the input could fill
+                let mut values = Vec::with_capacity(len.min(input.len()));
+                for _ in 0..len {
+                    values.push(self.decode_arg(arg, input, event_data)?);
+                }
+                Ok(Value::Sequence(values))
+            }
+            EventArg::Option(arg) => {
+                match input.read_byte()? {
+                    0 => Ok(Value::Variant("None".into(), vec![])),
+                    1 => {
+                        let value = self.decode_arg(arg, input, event_data)?;
+                        Ok(Value::Variant("Some".into(), vec![value]))
+                    }
+                    _ => {
+                        Err(Error::Other("unexpected first byte decoding Option".into()))
+                    }
+                }
+            }
+            EventArg::Tuple(args) => {
+                Ok(Value::Composite(
+                    self.decode_values(args, input, event_data)?,
+                ))
+            }
+            EventArg::Primitive(name) => {
+                self.decode_primitive_value(name, input, event_data)
+            }
+        }
+    }
+
+    fn decode_values(
+        &self,
+        args: &[EventArg],
+        input: &mut &[u8],
+        event_data: bool,
+    ) -> Result<Vec<Value>, Error> {
+        args.iter()
+            .map(|arg| self.decode_arg(arg, input, event_data))
+            .collect()
+    }
+
+    fn decode_primitive_value(
+        &self,
+        name: &str,
+        input: &mut &[u8],
+        event_data: bool,
+    ) -> Result<Value, Error> {
+        if event_data && name == "DispatchResult" {
+            // events with a failed dispatch are decoded as `Raw::Error` instead
+            Ok(Value::Variant("Ok".into(), vec![]))
+        } else if event_data && name == "DispatchError" {
+            Err(Error::Other(
+                "DispatchError is not part of the event data".into(),
+            ))
+        } else if name == "DispatchResult" {
+            match DispatchResult::decode(input)? {
+                Ok(()) => Ok(Value::Variant("Ok".into(), vec![])),
+                Err(error) => {
+                    let error = self.dispatch_error_value(error)?;
+                    Ok(Value::Variant("Err".into(), vec![error]))
+                }
+            }
+        } else if name == "DispatchError" {
+            self.dispatch_error_value(DispatchError::decode(input)?)
+        } else if let Some(primitive) = Primitive::decode_named(name, input) {
+            Ok(Value::Primitive(primitive?))
+        } else if let Some(inner) = name
+            .strip_prefix("Compact<")
+            .and_then(|name| name.strip_suffix('>'))
+        {
+            self.decode_compact_value(inner.trim(), input)
+        } else if let Some(seg) = self.event_type_registry.resolve(name) {
+            seg.value(input)
+        } else {
+            Err(Error::TypeSizeUnavailable(name.to_owned()))
+        }
+    }
+
+    fn decode_compact_value(
+        &self,
+        name: &str,
+        input: &mut &[u8],
+    ) -> Result<Value, Error> {
+        if let Some(primitive) = Primitive::decode_compact_named(name, input) {
+            Ok(Value::Primitive(primitive?))
+        } else if let Some(seg) = self.event_type_registry.resolve(name) {
+            seg.compact_value(input)
+        } else {
+            Err(Error::TypeSizeUnavailable(format!("Compact<{}>", name)))
+        }
+    }
+
+    fn dispatch_error_value(&self, error: DispatchError) -> Result<Value, Error> {
+        let value = match RuntimeError::from_dispatch(&self.metadata, error)? {
+            RuntimeError::Module(error) => {
+                Value::Variant(
+                    "Module".into(),
+                    vec![
+                        Value::Primitive(Primitive::Str(error.module)),
+                        Value::Primitive(Primitive::Str(error.error)),
+                    ],
+                )
+            }
+            RuntimeError::Other(message) => {
+                Value::Variant(
+                    "Other".into(),
+                    vec![Value::Primitive(Primitive::Str(message))],
+                )
+            }
+            error => Value::Variant(format!("{:?}", error), vec![]),
+        };
+        Ok(value)
+    }
 }
 
+/// Checks a definition against the metadata, see [`Validate`].
+type ValidateFn = fn(&Metadata) -> Result<(), MetadataError>;
+
 /// Registry for event types which cannot be directly inferred from the metadata.
+///
+/// Also holds the `Call`, `Event` and `Store` definitions registered by the `#[module]`s of
+/// the runtime, for `Client::validate_modules`.
 #[derive(Default)]
 pub struct EventTypeRegistry<T> {
     segmenters: HashMap<String, Box<dyn TypeSegmenter>>,
+    validators: Vec<ValidateFn>,
     marker: PhantomData<fn() -> T>,
 }
 
@@ -261,6 +618,7 @@ impl<T> Clone for EventTypeRegistry<T> {
     fn clone(&self) -> Self {
         Self {
             segmenters: self.segmenters.clone(),
+            validators: self.validators.clone(),
             marker: PhantomData,
         }
     }
@@ -273,6 +631,7 @@ impl<T> fmt::Debug for EventTypeRegistry<T> {
                 "segmenters",
                 &self.segmenters.keys().cloned().collect::<String>(),
             )
+            .field("validators", &self.validators.len())
             .finish()
     }
 }
@@ -282,6 +641,7 @@ impl<T: Runtime> EventTypeRegistry<T> {
     pub fn new() -> Self {
         let mut registry = Self {
             segmenters: HashMap::new(),
+            validators: Vec::new(),
             marker: PhantomData,
         };
         T::register_type_sizes(&mut registry);
@@ -305,6 +665,12 @@ impl<T: Runtime> EventTypeRegistry<T> {
         };
     }
 
+    /// Register a `Call`, `Event` or `Store` definition to be checked by
+    /// [`Validator::check_registered`](crate::Validator::check_registered).
+    pub fn register_validator<V: Validate<T>>(&mut self) {
+        self.validators.push(V::validate);
+    }
+
     /// Check missing type sizes.
     pub fn check_missing_type_sizes(
         &self,
@@ -341,6 +707,13 @@ impl<T: Runtime> EventTypeRegistry<T> {
     }
 }
 
+impl<T> EventTypeRegistry<T> {
+    /// Returns the registered definition checks.
+    pub(crate) fn validators(&self) -> &[ValidateFn] {
+        &self.validators
+    }
+}
+
 /// Raw event or error event
 #[derive(Debug)]
 pub enum Raw {
@@ -348,22 +721,36 @@ pub enum Raw {
     Event(RawEvent),
     /// Error
     Error(RuntimeError),
+    /// Event that could not be decoded, only returned by
+    /// [`EventsDecoder::decode_events_lenient`]
+    Undecodable {
+        /// The name of the module from whence the Event originated
+        module: String,
+        /// The name of the Event
+        variant: String,
+        /// Why the Event could not be decoded
+        error: Error,
+    },
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Validator;
     use frame_metadata::{
-        DecodeDifferent,
-        ErrorMetadata,
-        EventMetadata,
-        ExtrinsicMetadata,
-        ModuleMetadata,
+        decode_different::DecodeDifferent,
+        v12::{
+            ErrorMetadata,
+            EventMetadata,
+            ExtrinsicMetadata,
+            ModuleMetadata,
+            RuntimeMetadataV12,
+            META_RESERVED,
+        },
         RuntimeMetadata,
         RuntimeMetadataPrefixed,
-        RuntimeMetadataV12,
-        META_RESERVED,
     };
+    use sp_runtime::Perbill;
     use std::convert::TryFrom;
 
     type TestRuntime = crate::NodeTemplateRuntime;
@@ -394,6 +781,263 @@ mod tests {
         assert_eq!(output, vec![1, 0]);
     }
 
+    #[test]
+    fn test_decode_value() {
+        let decoder = EventsDecoder::<TestRuntime>::new(
+            Metadata::default(),
+            EventTypeRegistry::new(),
+        );
+
+        let input = (vec![1u8, 2], Some(5u32), (true, 7u128), vec![3u16]).encode();
+        let args = ["Vec<u8>", "Option<u32>", "(bool, Balance)", "Vec<u16>"]
+            .iter()
+            .map(|arg| arg.parse::<EventArg>().unwrap())
+            .collect::<Vec<_>>();
+        let values = decoder
+            .decode_values(&args, &mut &input[..], false)
+            .unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                Value::Primitive(Primitive::Bytes(vec![1, 2])),
+                Value::Variant("Some".into(), vec![Value::Primitive(Primitive::U32(5))]),
+                Value::Composite(vec![
+                    Value::Primitive(Primitive::Bool(true)),
+                    Value::Primitive(Primitive::U128(7)),
+                ]),
+                Value::Sequence(vec![Value::Primitive(Primitive::U16(3))]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_decode_value_checks_vec_len() {
+        let decoder = EventsDecoder::<TestRuntime>::new(
+            Metadata::default(),
+            EventTypeRegistry::new(),
+        );
+
+        let input = Compact(u32::MAX).encode();
+        let bytes = "Vec<u8>".parse::<EventArg>().unwrap();
+        assert!(decoder.decode_value(&bytes, &mut &input[..]).is_err());
+        let values = "Vec<u32>".parse::<EventArg>().unwrap();
+        assert!(decoder.decode_value(&values, &mut &input[..]).is_err());
+    }
+
+    #[test]
+    fn test_decode_compact_value() {
+        let decoder = EventsDecoder::<TestRuntime>::new(
+            Metadata::default(),
+            EventTypeRegistry::new(),
+        );
+
+        let input = (
+            Compact(u64::MAX),
+            Compact(Perbill::from_percent(10)),
+            Compact(7u128),
+        )
+            .encode();
+        let args = ["Compact<u64>", "Compact<Perbill>", "Compact<Balance>"]
+            .iter()
+            .map(|arg| arg.parse::<EventArg>().unwrap())
+            .collect::<Vec<_>>();
+        let values = decoder
+            .decode_values(&args, &mut &input[..], false)
+            .unwrap();
+
+        assert_eq!(
+            values,
+            vec![
+                Value::Primitive(Primitive::U64(u64::MAX)),
+                Value::Primitive(Primitive::U32(100_000_000)),
+                Value::Primitive(Primitive::U128(7)),
+            ]
+        );
+        let unknown = "Compact<Unknown>".parse::<EventArg>().unwrap();
+        assert!(matches!(
+            decoder.decode_value(&unknown, &mut &input[..]),
+            Err(Error::TypeSizeUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn test_segmenter_without_value() {
+        #[derive(Clone)]
+        struct ByteSegmenter;
+
+        impl TypeSegmenter for ByteSegmenter {
+            fn segment(
+                &self,
+                input: &mut &[u8],
+                output: &mut Vec<u8>,
+            ) -> Result<(), Error> {
+                output.push(input.read_byte()?);
+                Ok(())
+            }
+        }
+
+        let mut output = Vec::new();
+        ByteSegmenter.segment(&mut &[1u8][..], &mut output).unwrap();
+        assert_eq!(output, vec![1]);
+        assert!(ByteSegmenter.value(&mut &[1u8][..]).is_err());
+    }
+
+    #[test]
+    fn test_check_registered() {
+        struct Missing;
+
+        impl Validate<TestRuntime> for Missing {
+            fn validate(_: &Metadata) -> Result<(), MetadataError> {
+                Err(MetadataError::ModuleNotFound("Missing".to_string()))
+            }
+        }
+
+        let mut registry = EventTypeRegistry::<TestRuntime>::new();
+        let registered = registry.validators().len();
+        assert!(registered > 0);
+        registry.register_validator::<Missing>();
+
+        let metadata = Metadata::default();
+        let errors = Validator::new(&metadata)
+            .check_registered(&registry)
+            .finish()
+            .unwrap_err();
+        assert_eq!(errors.len(), registered + 1);
+        assert!(errors
+            .iter()
+            .all(|err| matches!(err, MetadataError::ModuleNotFound(_))));
+    }
+
+    #[test]
+    fn test_decode_event_value_with_dispatch_result() {
+        let decoder = EventsDecoder::<TestRuntime>::new(
+            Metadata::try_from(RuntimeMetadataPrefixed(
+                META_RESERVED,
+                RuntimeMetadata::V12(RuntimeMetadataV12 {
+                    modules: DecodeDifferent::Decoded(vec![ModuleMetadata {
+                        name: DecodeDifferent::Decoded("Sudo".to_string()),
+                        storage: None,
+                        calls: None,
+                        event: Some(DecodeDifferent::Decoded(vec![EventMetadata {
+                            name: DecodeDifferent::Decoded("Sudid".to_string()),
+                            arguments: DecodeDifferent::Decoded(vec![
+                                "DispatchResult".to_string()
+                            ]),
+                            documentation: DecodeDifferent::Decoded(vec![]),
+                        }])),
+                        constants: DecodeDifferent::Decoded(vec![]),
+                        errors: DecodeDifferent::Decoded(vec![]),
+                        index: 0,
+                    }]),
+                    extrinsic: ExtrinsicMetadata {
+                        version: 0,
+                        signed_extensions: vec![],
+                    },
+                }),
+            ))
+            .unwrap(),
+            EventTypeRegistry::new(),
+        );
+
+        // [(ApplyExtrinsic(0), Sudid(Ok(())))]
+        let input = hex::decode("04000000000000000000").unwrap();
+        let events = decoder.decode_events(&mut &input[..]).unwrap();
+        match &events[..] {
+            [(Phase::ApplyExtrinsic(0), Raw::Event(event))] => {
+                assert!(event.data.is_empty());
+                assert_eq!(
+                    decoder.decode_event_value(event).unwrap(),
+                    Value::Composite(vec![Value::Variant("Ok".into(), vec![])])
+                );
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_events_lenient() {
+        let decoder = EventsDecoder::<TestRuntime>::new(
+            Metadata::try_from(RuntimeMetadataPrefixed(
+                META_RESERVED,
+                RuntimeMetadata::V12(RuntimeMetadataV12 {
+                    modules: DecodeDifferent::Decoded(vec![ModuleMetadata {
+                        name: DecodeDifferent::Decoded("System".to_string()),
+                        storage: None,
+                        calls: None,
+                        event: Some(DecodeDifferent::Decoded(vec![
+                            EventMetadata {
+                                name: DecodeDifferent::Decoded(
+                                    "ExtrinsicSuccess".to_string(),
+                                ),
+                                arguments: DecodeDifferent::Decoded(vec![
+                                    "DispatchInfo".to_string()
+                                ]),
+                                documentation: DecodeDifferent::Decoded(vec![]),
+                            },
+                            EventMetadata {
+                                name: DecodeDifferent::Decoded("Unknown".to_string()),
+                                arguments: DecodeDifferent::Decoded(vec![
+                                    "UnknownType".to_string()
+                                ]),
+                                documentation: DecodeDifferent::Decoded(vec![]),
+                            },
+                        ])),
+                        constants: DecodeDifferent::Decoded(vec![]),
+                        errors: DecodeDifferent::Decoded(vec![]),
+                        index: 0,
+                    }]),
+                    extrinsic: ExtrinsicMetadata {
+                        version: 0,
+                        signed_extensions: vec![],
+                    },
+                }),
+            ))
+            .unwrap(),
+            EventTypeRegistry::new(),
+        );
+
+        // [(ApplyExtrinsic(0), Unknown(0x01020304)), (ApplyExtrinsic(1), ExtrinsicSuccess)]
+        let input =
+            hex::decode("0800000000000001010203040000010000000000482d7c0900000000020000")
+                .unwrap();
+        assert!(matches!(
+            decoder.decode_events(&mut &input[..]),
+            Err(Error::TypeSizeUnavailable(_))
+        ));
+
+        let events = decoder.decode_events_lenient(&mut &input[..]).unwrap();
+        match &events[..] {
+            [(
+                Phase::ApplyExtrinsic(0),
+                Raw::Undecodable {
+                    module, variant, ..
+                },
+            ), (Phase::ApplyExtrinsic(1), Raw::Event(event))] => {
+                assert_eq!(module, "System");
+                assert_eq!(variant, "Unknown");
+                assert_eq!(event.variant, "ExtrinsicSuccess");
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+
+        // [(ApplyExtrinsic(0), Unknown(0x01020304)), (ApplyExtrinsic(1), Unknown(0x0506)),
+        //  (ApplyExtrinsic(2), ExtrinsicSuccess)]
+        let input = hex::decode(
+            "0c000000000000010102030400000100000000010506000002000000\
+             0000482d7c0900000000020000",
+        )
+        .unwrap();
+        let events = decoder.decode_events_lenient(&mut &input[..]).unwrap();
+        match &events[..] {
+            [(Phase::ApplyExtrinsic(0), Raw::Undecodable { .. }), (Phase::ApplyExtrinsic(1), Raw::Undecodable { .. }), (Phase::ApplyExtrinsic(2), Raw::Event(event))] =>
+            {
+                assert_eq!(event.variant, "ExtrinsicSuccess");
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
+    }
+
     #[test]
     fn test_decode_system_events_and_error() {
         let decoder = EventsDecoder::<TestRuntime>::new(
@@ -467,5 +1111,18 @@ mod tests {
         // [(ApplyExtrinsic(0), Event(RawEvent { module: "System", variant: "ExtrinsicSuccess", data: "482d7c09000000000200" })), (ApplyExtrinsic(1), Error(Module(ModuleError { module: "System", error: "NonDefaultComposite" }))), (ApplyExtrinsic(2), Error(Module(ModuleError { module: "System", error: "NonDefaultComposite" })))]
         let input = hex::decode("0c00000000000000482d7c0900000000020000000100000000010300035884723300000000000000000200000000010300035884723300000000000000").unwrap();
         decoder.decode_events(&mut &input[..]).unwrap();
+
+        // a single ExtrinsicSuccess event with one topic
+        let topic = sp_core::H256::repeat_byte(7);
+        let mut input = hex::decode("0400000000000000482d7c09000000000200").unwrap();
+        input.extend(vec![topic].encode());
+        let events = decoder.decode_events(&mut &input[..]).unwrap();
+        match &events[..] {
+            [(Phase::ApplyExtrinsic(0), Raw::Event(event))] => {
+                assert_eq!(event.phase, Phase::ApplyExtrinsic(0));
+                assert_eq!(event.topics, vec![topic.encode()]);
+            }
+            other => panic!("unexpected events: {:?}", other),
+        }
     }
 }
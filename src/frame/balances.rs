@@ -34,7 +34,11 @@ use sp_runtime::traits::{
 use std::fmt::Debug;
 
 /// The subset of the `pallet_balances::Trait` that a client must implement.
-#[module]
+#[module(
+    calls(TransferCall),
+    events(TransferEvent),
+    stores(TotalIssuanceStore, LocksStore)
+)]
 pub trait Balances: System {
     /// The balance of an account.
     type Balance: Parameter
@@ -80,6 +84,14 @@ pub struct TotalIssuanceStore<T: Balances> {
     pub _runtime: PhantomData<T>,
 }
 
+/// The minimum balance required to keep an account alive.
+#[derive(Clone, Debug, Eq, PartialEq, Constant)]
+pub struct ExistentialDepositConstant<T: Balances> {
+    #[constant(returns = T::Balance)]
+    /// Runtime marker.
+    pub _runtime: PhantomData<T>,
+}
+
 /// The locks of the balances module.
 #[derive(Clone, Debug, Eq, PartialEq, Store, Encode, Decode)]
 pub struct LocksStore<'a, T: Balances> {
@@ -161,11 +173,17 @@ mod tests {
             Signer,
         },
         subscription::EventSubscription,
-        system::AccountStoreExt,
+        system::{
+            AccountStoreExt,
+            SystemEvent,
+        },
         tests::{
             test_node_process,
             TestRuntime,
         },
+        Phase,
+        RawEvent,
+        RuntimeEvent,
     };
     use sp_core::{
         sr25519::Pair,
@@ -173,6 +191,50 @@ mod tests {
     };
     use sp_keyring::AccountKeyring;
 
+    #[derive(Debug, PartialEq, RuntimeEvent)]
+    enum NodeEvent<T: Balances> {
+        System(SystemEvent<T>),
+        Balances(BalancesEvent<T>),
+    }
+
+    #[test]
+    fn test_decode_raw_event() {
+        let alice = AccountKeyring::Alice.to_account_id();
+        let bob = AccountKeyring::Bob.to_account_id();
+        let raw = |module: &str, variant: &str| {
+            RawEvent {
+                phase: Phase::ApplyExtrinsic(0),
+                module: module.to_string(),
+                variant: variant.to_string(),
+                data: (alice.clone(), bob.clone(), 10_000u128).encode(),
+                topics: vec![],
+            }
+        };
+        let transfer = TransferEvent {
+            from: alice.clone(),
+            to: bob.clone(),
+            amount: 10_000,
+        };
+        assert_eq!(
+            NodeEvent::<TestRuntime>::decode_raw(&raw("Balances", "Transfer")).unwrap(),
+            Some(NodeEvent::Balances(BalancesEvent::Transfer(transfer)))
+        );
+        // unknown module
+        assert_eq!(
+            NodeEvent::<TestRuntime>::decode_raw(&raw("Assets", "Transfer")).unwrap(),
+            None
+        );
+        // unknown event
+        assert_eq!(
+            NodeEvent::<TestRuntime>::decode_raw(&raw("Balances", "Deposit")).unwrap(),
+            None
+        );
+        // known event with invalid data
+        let mut truncated = raw("Balances", "Transfer");
+        truncated.data.truncate(40);
+        assert!(NodeEvent::<TestRuntime>::decode_raw(&truncated).is_err());
+    }
+
     #[async_std::test]
     async fn test_basic_transfer() {
         env_logger::try_init().ok();
@@ -215,6 +277,15 @@ mod tests {
         assert_ne!(total_issuance, 0);
     }
 
+    #[async_std::test]
+    async fn test_existential_deposit() {
+        env_logger::try_init().ok();
+        let test_node_proc = test_node_process().await;
+        let client = test_node_proc.client();
+        let existential_deposit = client.existential_deposit().unwrap();
+        assert_ne!(existential_deposit, 0);
+    }
+
     #[async_std::test]
     async fn test_state_read_free_balance() {
         env_logger::try_init().ok();
@@ -314,4 +385,37 @@ mod tests {
             }
         );
     }
+
+    #[async_std::test]
+    async fn test_transfer_subscription_filter_decoded() {
+        env_logger::try_init().ok();
+        let alice = PairSigner::<TestRuntime, _>::new(AccountKeyring::Alice.pair());
+        let bob = AccountKeyring::Bob.to_account_id();
+        let charlie = AccountKeyring::Charlie.to_account_id();
+        let test_node_proc = test_node_process().await;
+        let client = test_node_proc.client();
+        let sub = client.subscribe_events().await.unwrap();
+        let decoder = client.events_decoder();
+        let mut sub = EventSubscription::<TestRuntime>::new(sub, &decoder);
+        let to = charlie.clone();
+        sub.filter_decoded::<TransferEvent<TestRuntime>, _>(move |event| event.to == to);
+        client
+            .transfer(&alice, &bob.clone().into(), 10_000)
+            .await
+            .unwrap();
+        client
+            .transfer(&alice, &charlie.clone().into(), 20_000)
+            .await
+            .unwrap();
+        let raw = sub.next().await.unwrap().unwrap();
+        let event = TransferEvent::<TestRuntime>::decode(&mut &raw.data[..]).unwrap();
+        assert_eq!(
+            event,
+            TransferEvent {
+                from: alice.account_id().clone(),
+                to: charlie,
+                amount: 20_000,
+            }
+        );
+    }
 }
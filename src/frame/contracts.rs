@@ -30,7 +30,10 @@ use codec::{
 pub type Gas = u64;
 
 /// The subset of the `pallet_contracts::Trait` that a client must implement.
-#[module]
+#[module(
+    calls(InstantiateWithCodeCall, InstantiateCall, CallCall),
+    events(CodeStoredEvent, InstantiatedEvent, ContractEmittedEvent)
+)]
 pub trait Contracts: System + Balances {}
 
 /// Instantiates a new contract from the supplied `code` optionally transferring
@@ -120,13 +123,13 @@ pub struct InstantiatedEvent<T: Contracts> {
     pub contract: <T as System>::AccountId,
 }
 
-/// Contract execution event.
+/// Contract emitted event.
 ///
-/// Emitted upon successful execution of a contract, if any contract events were produced.
+/// A custom event emitted by a contract.
 #[derive(Clone, Debug, Eq, PartialEq, Event, Decode)]
-pub struct ContractExecutionEvent<T: Contracts> {
-    /// Caller of the contract.
-    pub caller: <T as System>::AccountId,
+pub struct ContractEmittedEvent<T: Contracts> {
+    /// The address of the contract which emitted the event.
+    pub contract: <T as System>::AccountId,
     /// SCALE encoded contract event data.
     pub data: Vec<u8>,
 }
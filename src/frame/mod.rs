@@ -16,15 +16,22 @@
 
 //! Implements support for built-in runtime modules.
 
-use crate::metadata::{
-    Metadata,
-    MetadataError,
+use crate::{
+    events::{
+        EventTypeRegistry,
+        RawEvent,
+    },
+    metadata::{
+        Metadata,
+        MetadataError,
+    },
 };
 use codec::{
     Decode,
     Encode,
 };
 use sp_core::storage::StorageKey;
+use std::marker::PhantomData;
 
 pub mod balances;
 pub mod contracts;
@@ -62,6 +69,23 @@ pub trait Call<T>: Encode {
     const FUNCTION: &'static str;
 }
 
+/// Constant trait.
+pub trait Constant<T> {
+    /// Module name.
+    const MODULE: &'static str;
+    /// Constant name.
+    const CONSTANT: &'static str;
+    /// Value type.
+    type Value: Decode;
+    /// Returns the value of the constant.
+    fn value(metadata: &Metadata) -> Result<Self::Value, MetadataError> {
+        metadata
+            .module(Self::MODULE)?
+            .constant(Self::CONSTANT)?
+            .value()
+    }
+}
+
 /// Event trait.
 pub trait Event<T>: Decode {
     /// Module name.
@@ -69,3 +93,69 @@ pub trait Event<T>: Decode {
     /// Event name.
     const EVENT: &'static str;
 }
+
+/// Typed event decoded from a `RawEvent`.
+///
+/// Implemented by each `Event` type, the `<Module>Event` enums generated by `#[module]`
+/// and runtime-wide enums deriving `RuntimeEvent`.
+pub trait RuntimeEvent<T>: Sized {
+    /// Decodes a raw event into the matching event or variant.
+    /// Returns `None` if the raw event is from another module or event.
+    fn decode_raw(raw: &RawEvent) -> Result<Option<Self>, codec::Error>;
+}
+
+/// Checks a `Call`, `Store` or `Event` definition against the runtime metadata.
+pub trait Validate<T> {
+    /// Returns an error if the definition is missing from the metadata or its fields
+    /// don't match.
+    ///
+    /// The fields of calls are only checked by number, those of events and storage
+    /// entries also by type name.
+    fn validate(metadata: &Metadata) -> Result<(), MetadataError>;
+}
+
+/// Validates several definitions at once, collecting all mismatches.
+pub struct Validator<'a, T> {
+    metadata: &'a Metadata,
+    errors: Vec<MetadataError>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T> Validator<'a, T> {
+    /// Creates a new validator.
+    pub fn new(metadata: &'a Metadata) -> Self {
+        Self {
+            metadata,
+            errors: Vec::new(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Validates the definition `V`.
+    pub fn check<V: Validate<T>>(mut self) -> Self {
+        if let Err(err) = V::validate(self.metadata) {
+            self.errors.push(err);
+        }
+        self
+    }
+
+    /// Validates the definitions registered by the `#[module]`s of the runtime, see
+    /// [`EventTypeRegistry::register_validator`].
+    pub fn check_registered(mut self, registry: &EventTypeRegistry<T>) -> Self {
+        for validate in registry.validators() {
+            if let Err(err) = validate(self.metadata) {
+                self.errors.push(err);
+            }
+        }
+        self
+    }
+
+    /// Returns all mismatches found.
+    pub fn finish(self) -> Result<(), Vec<MetadataError>> {
+        if self.errors.is_empty() {
+            Ok(())
+        } else {
+            Err(self.errors)
+        }
+    }
+}
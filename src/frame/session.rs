@@ -51,7 +51,7 @@ type IdentificationTuple<T> = (
 );
 
 /// The trait needed for this module.
-#[module]
+#[module(calls(SetKeysCall), stores(ValidatorsStore))]
 pub trait Session: System + Balances {
     #![event_alias(IdentificationTuple = IdentificationTuple<T>)]
     #![event_alias(OpaqueTimeSlot = Vec<u8>)]
@@ -60,7 +60,19 @@ pub struct SetPayeeCall<T: Staking> {
 }
 
 /// The subset of the `frame::Trait` that a client must implement.
-#[module]
+#[module(
+    calls(SetPayeeCall, ChillCall, ValidateCall, NominateCall, BondCall),
+    stores(
+        ErasRewardPointsStore,
+        HistoryDepthStore,
+        BondedStore,
+        LedgerStore,
+        PayeeStore,
+        ValidatorsStore,
+        NominatorsStore,
+        CurrentEraStore
+    )
+)]
 #[rustfmt::skip]
 pub trait Staking: Balances {
     #![event_alias(ElectionCompute = u8)]
@@ -25,7 +25,7 @@ use core::marker::PhantomData;
 use frame_support::weights::Weight;
 
 /// The subset of the `frame_sudo::Trait` that a client must implement.
-#[module]
+#[module(calls(SudoCall, SudoUncheckedWeightCall))]
 pub trait Sudo: System {}
 
 /// Execute a transaction with sudo permissions.
@@ -48,7 +48,17 @@ use sp_runtime::{
 use std::fmt::Debug;
 
 /// The subset of the `frame::Trait` that a client must implement.
-#[module]
+#[module(
+    calls(SetCodeCall, SetCodeWithoutChecksCall),
+    events(
+        ExtrinsicSuccessEvent,
+        ExtrinsicFailedEvent,
+        CodeUpdatedEvent,
+        NewAccountEvent,
+        KilledAccountEvent
+    ),
+    stores(AccountStore)
+)]
 pub trait System {
     /// Account index (aka nonce) type. This stores the number of previous
     /// transactions associated with a sender account.
@@ -51,11 +51,6 @@ use codec::{
     Decode,
 };
 use futures::future;
-use jsonrpsee_http_client::HttpClientBuilder;
-use jsonrpsee_ws_client::{
-    Subscription,
-    WsClientBuilder,
-};
 use sp_core::{
     storage::{
         StorageChangeSet,
@@ -68,7 +63,7 @@ pub use sp_runtime::traits::SignedExtension;
 pub use sp_version::RuntimeVersion;
 use std::{
     marker::PhantomData,
-    sync::Arc,
+    time::Duration,
 };
 
 mod error;
@@ -81,7 +76,10 @@ mod runtimes;
 mod subscription;
 #[cfg(test)]
 mod tests;
+mod value;
 
+#[cfg(unix)]
+pub use crate::rpc::IpcClient;
 pub use crate::{
     error::{
         Error,
@@ -91,6 +89,7 @@ pub use crate::{
     events::{
         EventTypeRegistry,
         EventsDecoder,
+        Raw,
         RawEvent,
     },
     extrinsic::{
@@ -101,23 +100,46 @@ pub use crate::{
     },
     frame::*,
     metadata::{
+        CallArg,
+        ItemChange,
         Metadata,
+        MetadataDiff,
         MetadataError,
+        ModuleCallMetadata,
+        ModuleDiff,
     },
     rpc::{
         BlockNumber,
         ExtrinsicSuccess,
+        FailoverClient,
+        FailoverClientBuilder,
+        Health,
         ReadProof,
+        RecordingTransport,
+        ReplayTransport,
+        RetryPolicy,
         RpcClient,
+        RpcTransport,
+        Subscription,
         SystemProperties,
+        TraceEntry,
     },
     runtimes::*,
     subscription::{
+        BestBlockSubscription,
+        BlockChange,
         EventStorageSubscription,
         EventSubscription,
         FinalizedEventStorageSubscription,
+        ReconnectingEventStorageSubscription,
+        StoreSubscription,
     },
     substrate_subxt_proc_macro::*,
+    value::{
+        DecodedCall,
+        Primitive,
+        Value,
+    },
 };
 use crate::{
     frame::system::{
@@ -129,17 +151,26 @@ use crate::{
         ChainBlock,
         Rpc,
     },
+    subscription::SystemEvents,
 };
 
 /// ClientBuilder for constructing a Client.
 #[derive(Default)]
 pub struct ClientBuilder<T: Runtime> {
     url: Option<String>,
+    urls: Vec<String>,
     client: Option<RpcClient>,
     page_size: Option<u32>,
     event_type_registry: EventTypeRegistry<T>,
     skip_type_sizes_check: bool,
     accept_weak_inclusion: bool,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    method_timeouts: Vec<(String, Duration)>,
+    metadata: Option<Metadata>,
+    genesis_hash: Option<T::Hash>,
+    runtime_version: Option<RuntimeVersion>,
+    properties: Option<SystemProperties>,
 }
 
 impl<T: Runtime> ClientBuilder<T> {
@@ -147,26 +178,49 @@ impl<T: Runtime> ClientBuilder<T> {
     pub fn new() -> Self {
         Self {
             url: None,
+            urls: Vec::new(),
             client: None,
             page_size: None,
             event_type_registry: EventTypeRegistry::new(),
             skip_type_sizes_check: false,
             accept_weak_inclusion: false,
+            retry_policy: Default::default(),
+            request_timeout: None,
+            method_timeouts: Vec::new(),
+            metadata: None,
+            genesis_hash: None,
+            runtime_version: None,
+            properties: None,
         }
     }
 
-    /// Sets the jsonrpsee client.
+    /// Sets the rpc client, e.g. a jsonrpsee client or a custom [`RpcTransport`].
     pub fn set_client<C: Into<RpcClient>>(mut self, client: C) -> Self {
         self.client = Some(client.into());
         self
     }
 
-    /// Set the substrate rpc address.
+    /// Set the substrate rpc address, e.g. `ws://127.0.0.1:9944`, `http://127.0.0.1:9933`
+    /// or the IPC socket `ipc:///tmp/substrate.ipc`.
     pub fn set_url<P: Into<String>>(mut self, url: P) -> Self {
         self.url = Some(url.into());
         self
     }
 
+    /// Set several substrate rpc addresses, in order of preference.
+    ///
+    /// Requests are sent to the first healthy node, failing over to the next one if it
+    /// goes down. See [`FailoverClient`] for configuring the health checks.
+    ///
+    /// Can't be combined with [`ClientBuilder::set_url`], `build` fails if both are set.
+    pub fn set_urls<P: Into<String>>(
+        mut self,
+        urls: impl IntoIterator<Item = P>,
+    ) -> Self {
+        self.urls = urls.into_iter().map(Into::into).collect();
+        self
+    }
+
     /// Set the page size.
     pub fn set_page_size(mut self, size: u32) -> Self {
         self.page_size = Some(size);
@@ -201,32 +255,94 @@ impl<T: Runtime> ClientBuilder<T> {
         self
     }
 
+    /// Set the policy for retrying failed requests.
+    ///
+    /// Requests with side effects, like submitting an extrinsic, are never retried.
+    pub fn set_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set the timeout of requests.
+    pub fn set_request_timeout(mut self, timeout: Duration) -> Self {
+        self.request_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the timeout of requests of the given rpc method, e.g. `state_getMetadata`,
+    /// overriding the timeout set with `set_request_timeout`.
+    pub fn set_method_timeout<M: Into<String>>(
+        mut self,
+        method: M,
+        timeout: Duration,
+    ) -> Self {
+        self.method_timeouts.push((method.into(), timeout));
+        self
+    }
+
+    /// Use the given metadata instead of fetching it from the node, e.g. restored from a
+    /// cached blob with [`Metadata::from_encoded`].
+    ///
+    /// To build a client without a node, e.g. for offline tools decoding data, also set
+    /// the genesis hash and runtime version, and pass a transport which doesn't connect to
+    /// [`ClientBuilder::set_client`], e.g. an empty [`ReplayTransport`]. The system
+    /// properties default to empty ones then, and requests to the node fail.
+    ///
+    /// *WARNING* the metadata must match the runtime of the node.
+    pub fn set_metadata(mut self, metadata: Metadata) -> Self {
+        self.metadata = Some(metadata);
+        self
+    }
+
+    /// Use the given genesis hash instead of fetching it from the node.
+    pub fn set_genesis_hash(mut self, genesis_hash: T::Hash) -> Self {
+        self.genesis_hash = Some(genesis_hash);
+        self
+    }
+
+    /// Use the given runtime version instead of fetching it from the node.
+    pub fn set_runtime_version(mut self, runtime_version: RuntimeVersion) -> Self {
+        self.runtime_version = Some(runtime_version);
+        self
+    }
+
+    /// Use the given system properties instead of fetching them from the node.
+    pub fn set_system_properties(mut self, properties: SystemProperties) -> Self {
+        self.properties = Some(properties);
+        self
+    }
+
     /// Creates a new Client.
     pub async fn build<'a>(self) -> Result<Client<T>, Error> {
+        if self.url.is_some() && !self.urls.is_empty() {
+            return Err("Set either a url or several urls, not both".into())
+        }
         let client = if let Some(client) = self.client {
             client
+        } else if !self.urls.is_empty() {
+            FailoverClientBuilder::new(self.urls).build().await?.into()
         } else {
-            let url = self.url.as_deref().unwrap_or("ws://127.0.0.1:9944");
-            if url.starts_with("ws://") || url.starts_with("wss://") {
-                let client = WsClientBuilder::default()
-                    .max_notifs_per_subscription(4096)
-                    .build(url)
-                    .await?;
-                RpcClient::WebSocket(Arc::new(client))
-            } else {
-                let client = HttpClientBuilder::default().build(&url)?;
-                RpcClient::Http(Arc::new(client))
-            }
+            let url = self
+                .url
+                .unwrap_or_else(|| "ws://127.0.0.1:9944".to_string());
+            RpcClient::connect(&url).await?
         };
         let mut rpc = Rpc::new(client);
         if self.accept_weak_inclusion {
             rpc.accept_weak_inclusion();
         }
+        rpc.set_retry_policy(self.retry_policy);
+        if let Some(timeout) = self.request_timeout {
+            rpc.set_request_timeout(timeout);
+        }
+        for (method, timeout) in self.method_timeouts {
+            rpc.set_method_timeout(method, timeout);
+        }
         let (metadata, genesis_hash, runtime_version, properties) = future::join4(
-            rpc.metadata(),
-            rpc.genesis_hash(),
-            rpc.runtime_version(None),
-            rpc.system_properties(),
+            or_fetch(self.metadata, rpc.metadata()),
+            or_fetch(self.genesis_hash, rpc.genesis_hash()),
+            or_fetch(self.runtime_version, rpc.runtime_version(None)),
+            or_fetch(self.properties, rpc.system_properties()),
         )
         .await;
         let metadata = metadata?;
@@ -264,6 +380,17 @@ impl<T: Runtime> ClientBuilder<T> {
     }
 }
 
+/// Returns the supplied value, or awaits `fetch` if there is none.
+async fn or_fetch<V, F>(value: Option<V>, fetch: F) -> Result<V, Error>
+where
+    F: std::future::Future<Output = Result<V, Error>>,
+{
+    match value {
+        Some(value) => Ok(value),
+        None => fetch.await,
+    }
+}
+
 /// Client to interface with a substrate node.
 pub struct Client<T: Runtime> {
     rpc: Rpc<T>,
@@ -348,11 +475,52 @@ impl<T: Runtime> Client<T> {
         &self.metadata
     }
 
+    /// Fetch a constant from the chain metadata.
+    pub fn constant<C: Constant<T>>(&self) -> Result<C::Value, Error> {
+        Ok(C::value(&self.metadata)?)
+    }
+
+    /// Checks a `Call`, `Store` or `Event` definition against the chain metadata.
+    ///
+    /// ```ignore
+    /// client.validate::<TransferCall<T>>()?;
+    /// ```
+    pub fn validate<V: Validate<T>>(&self) -> Result<(), MetadataError> {
+        V::validate(&self.metadata)
+    }
+
+    /// Returns a `Validator` for checking several definitions against the chain metadata.
+    ///
+    /// ```ignore
+    /// let mismatches: Result<(), Vec<MetadataError>> = client
+    ///     .validator()
+    ///     .check::<TransferCall<T>>()
+    ///     .check::<AccountStore<T>>()
+    ///     .check::<TransferEvent<T>>()
+    ///     .finish();
+    /// ```
+    pub fn validator(&self) -> Validator<'_, T> {
+        Validator::new(&self.metadata)
+    }
+
+    /// Checks the definitions listed in the `#[module]`s registered by the runtime against
+    /// the chain metadata, returning all mismatches.
+    pub fn validate_modules(&self) -> Result<(), Vec<MetadataError>> {
+        self.validator()
+            .check_registered(self.events_decoder.event_type_registry())
+            .finish()
+    }
+
     /// Returns the system properties
     pub fn properties(&self) -> &SystemProperties {
         &self.properties
     }
 
+    /// Returns the runtime version.
+    pub fn runtime_version(&self) -> &RuntimeVersion {
+        &self.runtime_version
+    }
+
     /// Returns the rpc client.
     pub fn rpc_client(&self) -> &RpcClient {
         &self.rpc.client
@@ -381,6 +549,47 @@ impl<T: Runtime> Client<T> {
         self.fetch_unhashed::<F::Returns>(key, hash).await
     }
 
+    /// Fetch many StorageKeys with an optional block hash in JSON-RPC batches, returning
+    /// the values in the order of the stores.
+    pub async fn fetch_many<F: Store<T>>(
+        &self,
+        stores: &[F],
+        hash: Option<T::Hash>,
+    ) -> Result<Vec<Option<F::Returns>>, Error> {
+        let keys = stores
+            .iter()
+            .map(|store| store.key(&self.metadata))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.rpc
+            .storage_many(&keys, hash)
+            .await?
+            .into_iter()
+            .map(|data| {
+                data.map(|data| Decode::decode(&mut &data.0[..]))
+                    .transpose()
+                    .map_err(Into::into)
+            })
+            .collect()
+    }
+
+    /// Fetch a StorageKey with an optional block hash, decoding it as a dynamic [`Value`]
+    /// using the type from the metadata.
+    pub async fn fetch_value<F: Store<T>>(
+        &self,
+        store: &F,
+        hash: Option<T::Hash>,
+    ) -> Result<Option<Value>, Error> {
+        let key = store.key(&self.metadata)?;
+        let storage = self.metadata.module(F::MODULE)?.storage(F::FIELD)?;
+        if let Some(data) = self.rpc.storage(&key, hash).await? {
+            Ok(Some(
+                self.events_decoder.decode_storage_value(storage, &data.0)?,
+            ))
+        } else {
+            Ok(None)
+        }
+    }
+
     /// Fetch a StorageKey that has a default value with an optional block hash.
     pub async fn fetch_or_default<F: Store<T>>(
         &self,
@@ -452,6 +661,16 @@ impl<T: Runtime> Client<T> {
         Ok(header)
     }
 
+    /// Get many block headers in JSON-RPC batches, returning the headers in the order of
+    /// the hashes.
+    pub async fn headers(
+        &self,
+        hashes: &[T::Hash],
+    ) -> Result<Vec<Option<T::Header>>, Error> {
+        let headers = self.rpc.headers(hashes).await?;
+        Ok(headers)
+    }
+
     /// Get a block hash. By default returns the latest block hash
     pub async fn block_hash(
         &self,
@@ -461,6 +680,12 @@ impl<T: Runtime> Client<T> {
         Ok(hash)
     }
 
+    /// Get the health of the node
+    pub async fn system_health(&self) -> Result<Health, Error> {
+        let health = self.rpc.system_health().await?;
+        Ok(health)
+    }
+
     /// Get a block hash of the latest finalized block
     pub async fn finalized_head(&self) -> Result<T::Hash, Error> {
         let head = self.rpc.finalized_head().await?;
@@ -489,6 +714,98 @@ impl<T: Runtime> Client<T> {
         Ok(proof)
     }
 
+    /// Fetch and decode the events of the block with the given hash.
+    pub async fn events_at(&self, hash: T::Hash) -> Result<Vec<(Phase, Raw)>, Error> {
+        let key = StorageKey::from(SystemEvents::new());
+        if let Some(data) = self.rpc.storage(&key, Some(hash)).await? {
+            self.events_decoder.decode_events(&mut &data.0[..])
+        } else {
+            Ok(Vec::new())
+        }
+    }
+
+    /// Fetch and decode the events of the blocks `from..=to`, by block number.
+    ///
+    /// The block hashes and events are fetched in JSON-RPC batches. Returns an error if
+    /// any block in the range does not exist.
+    pub async fn events_range(
+        &self,
+        from: u32,
+        to: u32,
+    ) -> Result<Vec<(T::Hash, Vec<(Phase, Raw)>)>, Error> {
+        self.fetch_events_range(from, to, EventsDecoder::decode_events)
+            .await
+    }
+
+    /// Fetch and decode the events of the blocks `from..=to` like
+    /// [`Client::events_range`], reporting events which can't be decoded as
+    /// [`Raw::Undecodable`], see [`EventsDecoder::decode_events_lenient`].
+    pub async fn events_range_lenient(
+        &self,
+        from: u32,
+        to: u32,
+    ) -> Result<Vec<(T::Hash, Vec<(Phase, Raw)>)>, Error> {
+        self.fetch_events_range(from, to, EventsDecoder::decode_events_lenient)
+            .await
+    }
+
+    async fn fetch_events_range(
+        &self,
+        from: u32,
+        to: u32,
+        decode: fn(&EventsDecoder<T>, &mut &[u8]) -> Result<Vec<(Phase, Raw)>, Error>,
+    ) -> Result<Vec<(T::Hash, Vec<(Phase, Raw)>)>, Error> {
+        let numbers = (from..=to).map(BlockNumber::from).collect::<Vec<_>>();
+        let hashes = self
+            .rpc
+            .block_hashes(&numbers)
+            .await?
+            .into_iter()
+            .zip(from..=to)
+            .map(|(hash, number)| {
+                hash.ok_or_else(|| Error::Other(format!("Block {} not found", number)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = StorageKey::from(SystemEvents::new());
+        let data = self.rpc.storage_at_blocks(&key, &hashes).await?;
+        hashes
+            .into_iter()
+            .zip(data)
+            .map(|(hash, data)| {
+                let events = match data {
+                    Some(data) => decode(&self.events_decoder, &mut &data.0[..])?,
+                    None => Vec::new(),
+                };
+                Ok((hash, events))
+            })
+            .collect()
+    }
+
+    /// Subscribe to changes of a storage value, e.g. the balance of an account.
+    ///
+    /// The current value is returned first.
+    pub async fn subscribe_store<F: Store<T>>(
+        &self,
+        store: &F,
+    ) -> Result<StoreSubscription<T, F::Returns>, Error> {
+        self.subscribe_stores(std::slice::from_ref(store)).await
+    }
+
+    /// Subscribe to changes of several storage values of the same `Store`.
+    ///
+    /// Use [`StoreSubscription::next_with_index`] to find out which of the `stores` changed.
+    pub async fn subscribe_stores<F: Store<T>>(
+        &self,
+        stores: &[F],
+    ) -> Result<StoreSubscription<T, F::Returns>, Error> {
+        let keys = stores
+            .iter()
+            .map(|store| store.key(&self.metadata))
+            .collect::<Result<Vec<_>, _>>()?;
+        let subscription = self.rpc.subscribe_storage(keys.clone()).await?;
+        Ok(StoreSubscription::new(subscription, keys))
+    }
+
     /// Subscribe to events.
     ///
     /// *WARNING* these may not be included in the finalized chain, use
@@ -506,12 +823,66 @@ impl<T: Runtime> Client<T> {
         Ok(events)
     }
 
+    /// Subscribe to events, reconnecting when the connection is lost.
+    ///
+    /// Events of blocks imported while disconnected are fetched after reconnecting, so no
+    /// block is skipped. Requires a transport supporting [`RpcTransport::reconnect`], e.g.
+    /// a client built from a url or a [`FailoverClient`], and fails otherwise. The
+    /// subscription ends if reconnecting keeps failing, see
+    /// [`ReconnectingEventStorageSubscription::set_max_reconnect_attempts`].
+    pub async fn subscribe_events_reconnecting(
+        &self,
+    ) -> Result<EventStorageSubscription<T>, Error> {
+        self.subscribe_reconnecting(false).await
+    }
+
+    /// Subscribe to finalized events, reconnecting when the connection is lost.
+    ///
+    /// Events of blocks finalized while disconnected are fetched after reconnecting, so no
+    /// block is skipped. Requires a transport supporting [`RpcTransport::reconnect`], e.g.
+    /// a client built from a url or a [`FailoverClient`], and fails otherwise. The
+    /// subscription ends if reconnecting keeps failing, see
+    /// [`ReconnectingEventStorageSubscription::set_max_reconnect_attempts`].
+    pub async fn subscribe_finalized_events_reconnecting(
+        &self,
+    ) -> Result<EventStorageSubscription<T>, Error> {
+        self.subscribe_reconnecting(true).await
+    }
+
+    async fn subscribe_reconnecting(
+        &self,
+        finalized: bool,
+    ) -> Result<EventStorageSubscription<T>, Error> {
+        if !self.rpc.client.can_reconnect() {
+            return Err("Reconnecting not supported by the transport of the client".into())
+        }
+        let subscription = if finalized {
+            self.rpc.subscribe_finalized_events().await?
+        } else {
+            self.rpc.subscribe_events().await?
+        };
+        let subscription = ReconnectingEventStorageSubscription::new(
+            finalized,
+            self.rpc.clone(),
+            subscription,
+        );
+        Ok(EventStorageSubscription::Reconnecting(Box::new(
+            subscription,
+        )))
+    }
+
     /// Subscribe to new blocks.
     pub async fn subscribe_blocks(&self) -> Result<Subscription<T::Header>, Error> {
         let headers = self.rpc.subscribe_blocks().await?;
         Ok(headers)
     }
 
+    /// Subscribe to changes of the best chain, reporting blocks retracted by reorgs.
+    pub async fn subscribe_best_blocks(&self) -> Result<BestBlockSubscription<T>, Error> {
+        let headers = self.rpc.subscribe_blocks().await?;
+        Ok(BestBlockSubscription::new(self.rpc.clone(), headers))
+    }
+
     /// Subscribe to finalized blocks.
     pub async fn subscribe_finalized_blocks(
         &self,
@@ -17,6 +17,7 @@
 use std::{
     collections::HashMap,
     convert::TryFrom,
+    fmt,
     marker::PhantomData,
     str::FromStr,
 };
@@ -28,18 +29,30 @@ use codec::{
 };
 
 use frame_metadata::{
-    DecodeDifferent,
+    decode_different::DecodeDifferent,
+    v12::RuntimeMetadataV12,
+    v13::{
+        RuntimeMetadataV13,
+        StorageEntryModifier,
+        StorageEntryType,
+        StorageHasher,
+        META_RESERVED,
+    },
     RuntimeMetadata,
     RuntimeMetadataPrefixed,
-    StorageEntryModifier,
-    StorageEntryType,
-    StorageHasher,
-    META_RESERVED,
 };
 use sp_core::storage::StorageKey;
 
 use crate::Encoded;
 
+mod diff;
+
+pub use diff::{
+    ItemChange,
+    MetadataDiff,
+    ModuleDiff,
+};
+
 /// Metadata error.
 #[derive(Debug, thiserror::Error)]
 pub enum MetadataError {
@@ -54,11 +67,17 @@ pub enum MetadataError {
     ModuleIndexNotFound(u8),
     /// Call is not in metadata.
     #[error("Call {0} not found")]
-    CallNotFound(&'static str),
+    CallNotFound(String),
+    /// Call is not in metadata.
+    #[error("Call index {0} not found")]
+    CallIndexNotFound(u8),
     /// Event is not in metadata.
     #[error("Event {0} not found")]
     EventNotFound(u8),
     /// Event is not in metadata.
+    #[error("Event {0} not found")]
+    EventNameNotFound(String),
+    /// Event is not in metadata.
     #[error("Error {0} not found")]
     ErrorNotFound(u8),
     /// Storage is not in metadata.
@@ -67,6 +86,18 @@ pub enum MetadataError {
     /// Storage type does not match requested type.
     #[error("Storage type error")]
     StorageTypeError,
+    /// Number of storage keys does not match the number of hashers.
+    #[error("Expected {0} storage keys, got {1}")]
+    StorageKeysMismatch(usize, usize),
+    /// Number of fields of a definition does not match the metadata.
+    #[error("{0} has {1} fields in the metadata, but the definition has {2}")]
+    FieldCountMismatch(String, usize, usize),
+    /// Type of a field of a definition does not match the metadata.
+    #[error("{0} has type {1} in the metadata, but the definition has {2}")]
+    TypeMismatch(String, String, String),
+    /// Failure to decode metadata.
+    #[error("Failed to decode metadata: {0}")]
+    DecodeError(CodecError),
     /// Default error.
     #[error("Failed to decode default: {0}")]
     DefaultError(CodecError),
@@ -85,9 +116,25 @@ pub struct Metadata {
     modules_with_calls: HashMap<String, ModuleWithCalls>,
     modules_with_events: HashMap<String, ModuleWithEvents>,
     modules_with_errors: HashMap<String, ModuleWithErrors>,
+    encoded: Vec<u8>,
 }
 
 impl Metadata {
+    /// Decodes and converts SCALE encoded `RuntimeMetadataPrefixed`, e.g. a blob previously
+    /// returned by [`Metadata::encoded`] or by the `state_getMetadata` RPC.
+    pub fn from_encoded(bytes: &[u8]) -> Result<Self, MetadataError> {
+        let metadata = RuntimeMetadataPrefixed::decode(&mut &bytes[..])
+            .map_err(MetadataError::DecodeError)?;
+        Self::try_from(metadata)
+    }
+
+    /// Returns the SCALE encoded `RuntimeMetadataPrefixed` this metadata was converted from.
+    ///
+    /// Can be cached and restored with [`Metadata::from_encoded`].
+    pub fn encoded(&self) -> &[u8] {
+        &self.encoded
+    }
+
     /// Returns `ModuleMetadata`.
     pub fn module<S>(&self, name: S) -> Result<&ModuleMetadata, MetadataError>
     where
@@ -110,6 +157,22 @@ impl Metadata {
             .ok_or(MetadataError::ModuleNotFound(name))
     }
 
+    /// Returns `ModuleWithCalls` by module index.
+    pub fn module_with_calls_by_index(
+        &self,
+        module_index: u8,
+    ) -> Result<&ModuleWithCalls, MetadataError> {
+        self.modules_with_calls
+            .values()
+            .find(|&module| module.index == module_index)
+            .ok_or(MetadataError::ModuleIndexNotFound(module_index))
+    }
+
+    /// Returns Iterator of `ModuleWithCalls`.
+    pub fn modules_with_calls(&self) -> impl Iterator<Item = &ModuleWithCalls> {
+        self.modules_with_calls.values()
+    }
+
     /// Returns Iterator of `ModuleWithEvents`.
     pub fn modules_with_events(&self) -> impl Iterator<Item = &ModuleWithEvents> {
         self.modules_with_events.values()
@@ -126,6 +189,20 @@ impl Metadata {
             .ok_or(MetadataError::ModuleIndexNotFound(module_index))
     }
 
+    /// Returns `ModuleWithEvents` by module name.
+    pub fn module_with_events_by_name<S>(
+        &self,
+        name: S,
+    ) -> Result<&ModuleWithEvents, MetadataError>
+    where
+        S: ToString,
+    {
+        let name = name.to_string();
+        self.modules_with_events
+            .get(&name)
+            .ok_or(MetadataError::ModuleNotFound(name))
+    }
+
     /// Returns `ModuleWithErrors`.
     pub fn module_with_errors(
         &self,
@@ -137,6 +214,69 @@ impl Metadata {
             .ok_or(MetadataError::ModuleIndexNotFound(module_index))
     }
 
+    /// Checks that a call with arguments of the types `arg_tys` exists in the metadata.
+    ///
+    /// The metadata names the encoded argument types, so `#[codec(compact)]` fields are
+    /// passed as `Compact<_>`. Types are compared by name, ignoring paths and mapping the
+    /// names of definitions which differ from the runtime, e.g. `&'a [u8]` for `Vec<u8>`.
+    pub fn validate_call(
+        &self,
+        module: &str,
+        function: &str,
+        arg_tys: &[&str],
+    ) -> Result<(), MetadataError> {
+        let call = self.module_with_calls(module)?.call_metadata(function)?;
+        check_field_count(module, function, call.arguments.len(), arg_tys.len())?;
+        for (arg, ty) in call.arguments.iter().zip(arg_tys) {
+            let name = format!("{}.{}", function, arg.name);
+            check_type(module, &name, &arg.ty, ty)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that an event with arguments of the types `arg_tys` exists in the metadata.
+    ///
+    /// Types are compared by name, ignoring paths and mapping the names of definitions
+    /// which differ from the runtime, e.g. `T::Balance` for `BalanceOf<T>`.
+    pub fn validate_event(
+        &self,
+        module: &str,
+        event: &str,
+        arg_tys: &[&str],
+    ) -> Result<(), MetadataError> {
+        let event_metadata = self
+            .module_with_events_by_name(module)?
+            .event_by_name(event)?;
+        check_field_count(module, event, event_metadata.arguments.len(), arg_tys.len())?;
+        for (index, (arg, ty)) in event_metadata.arguments.iter().zip(arg_tys).enumerate()
+        {
+            let name = format!("{}.{}", event, index);
+            check_type(module, &name, &arg.to_string(), ty)?;
+        }
+        Ok(())
+    }
+
+    /// Checks that a storage entry with keys of the types `key_tys` and values of the
+    /// type `value_ty` exists in the metadata.
+    ///
+    /// Types are compared by name, ignoring paths and mapping the names of definitions
+    /// which differ from the runtime, e.g. `T::Balance` for `BalanceOf<T>`.
+    pub fn validate_storage(
+        &self,
+        module: &str,
+        field: &'static str,
+        key_tys: &[&str],
+        value_ty: &str,
+    ) -> Result<(), MetadataError> {
+        let storage = self.module(module)?.storage(field)?;
+        check_field_count(module, field, storage.key_tys.len(), key_tys.len())?;
+        for (index, (key_ty, ty)) in storage.key_tys.iter().zip(key_tys).enumerate() {
+            let name = format!("{}.{}", field, index);
+            check_type(module, &name, key_ty, ty)?;
+        }
+        check_type(module, field, &storage.value_ty, value_ty)
+    }
+
     /// Pretty print metadata.
     pub fn pretty(&self) -> String {
         let mut string = String::new();
@@ -167,6 +307,146 @@ impl Metadata {
     }
 }
 
+fn check_field_count(
+    module: &str,
+    name: &str,
+    expected: usize,
+    got: usize,
+) -> Result<(), MetadataError> {
+    if expected != got {
+        return Err(MetadataError::FieldCountMismatch(
+            format!("{}::{}", module, name),
+            expected,
+            got,
+        ))
+    }
+    Ok(())
+}
+
+fn check_type(
+    module: &str,
+    name: &str,
+    expected: &str,
+    got: &str,
+) -> Result<(), MetadataError> {
+    if !type_names_match(expected, got) {
+        return Err(MetadataError::TypeMismatch(
+            format!("{}::{}", module, name),
+            expected.to_string(),
+            got.to_string(),
+        ))
+    }
+    Ok(())
+}
+
+/// Type names of definitions which differ from the type names in the metadata.
+///
+/// Both type names are normalized before they are compared, i.e. references, paths and
+/// whitespace are stripped, so `&'a <T as System>::AccountId`, `T::AccountId` and
+/// `AccountId` match. Afterwards every (generic argument) type equal to the left side is
+/// replaced by the right side.
+const TYPE_ALIASES: &[(&str, &str)] = &[
+    // the balance type of the `Currency` of e.g. contracts and staking
+    ("BalanceOf<T>", "Balance"),
+    // calls take the `Source` of the `StaticLookup` of the runtime as address
+    ("Source", "Address"),
+    ("LookupSource", "Address"),
+    // contracts are identified by the hash of their code
+    ("CodeHash<T>", "Hash"),
+    ("Gas", "Weight"),
+    // calls are built from borrowed and pre-encoded arguments
+    ("[u8]", "Vec<u8>"),
+    ("Encoded", "Box<Call>"),
+    // the `AccountInfo` of the system module is generic over the runtime
+    ("AccountInfo<T>", "AccountInfo<Index,AccountData>"),
+];
+
+/// Returns `true` if the type name from the metadata matches the type of a definition.
+///
+/// The metadata only contains the type names as written in the runtime, so the names and
+/// all their generic arguments are compared after normalizing them, see [`TYPE_ALIASES`].
+fn type_names_match(metadata_ty: &str, ty: &str) -> bool {
+    normalize_type_name(&strip_type_name(metadata_ty))
+        == normalize_type_name(&strip_type_name(ty))
+}
+
+/// Strips a reference and the whitespace from a type name.
+fn strip_type_name(ty: &str) -> String {
+    let mut ty = ty.trim();
+    if let Some(rest) = ty.strip_prefix('&') {
+        ty = rest.trim_start();
+        if let Some(rest) = ty.strip_prefix('\'') {
+            ty = rest
+                .trim_start_matches(|c: char| c.is_alphanumeric() || c == '_')
+                .trim_start();
+        }
+        if let Some(rest) = ty.strip_prefix("mut ") {
+            ty = rest.trim_start();
+        }
+    }
+    ty.chars().filter(|c| !c.is_whitespace()).collect()
+}
+
+/// Splits `s` at the top-level occurrences of `separator`.
+fn split_top_level<'a>(s: &'a str, separator: &str) -> Vec<&'a str> {
+    let mut parts = Vec::new();
+    let mut depth = 0i32;
+    let mut start = 0;
+    let mut index = 0;
+    while index < s.len() {
+        let rest = &s[index..];
+        if depth == 0 && rest.starts_with(separator) {
+            parts.push(&s[start..index]);
+            index += separator.len();
+            start = index;
+            continue
+        }
+        match rest.as_bytes()[0] {
+            b'<' | b'(' | b'[' => depth += 1,
+            b'>' | b')' | b']' => depth -= 1,
+            _ => {}
+        }
+        index += 1;
+    }
+    parts.push(&s[start..]);
+    parts
+}
+
+fn normalize_type_name(ty: &str) -> String {
+    if ty.starts_with('(') && ty.ends_with(')') {
+        let elems = split_top_level(&ty[1..ty.len() - 1], ",")
+            .into_iter()
+            .filter(|elem| !elem.is_empty())
+            .map(normalize_type_name)
+            .collect::<Vec<_>>();
+        return format!("({})", elems.join(","))
+    }
+    let segment = split_top_level(ty, "::").pop().unwrap_or(ty);
+    let (name, args) = match segment.find('<') {
+        Some(start) if segment.ends_with('>') => {
+            (
+                &segment[..start],
+                Some(&segment[start + 1..segment.len() - 1]),
+            )
+        }
+        _ => (segment, None),
+    };
+    let ty = match args {
+        Some(args) => {
+            let args = split_top_level(args, ",")
+                .into_iter()
+                .map(normalize_type_name)
+                .collect::<Vec<_>>();
+            format!("{}<{}>", name, args.join(","))
+        }
+        None => name.to_string(),
+    };
+    match TYPE_ALIASES.iter().find(|(alias, _)| *alias == ty) {
+        Some((_, aliased)) => aliased.to_string(),
+        None => ty,
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ModuleMetadata {
     index: u8,
@@ -176,6 +456,15 @@ pub struct ModuleMetadata {
 }
 
 impl ModuleMetadata {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns an iterator over all storage entries of the module
+    pub fn storage_entries(&self) -> impl Iterator<Item = &StorageMetadata> {
+        self.storage.values()
+    }
+
     pub fn storage(&self, key: &'static str) -> Result<&StorageMetadata, MetadataError> {
         self.storage
             .get(key)
@@ -196,23 +485,85 @@ impl ModuleMetadata {
 #[derive(Clone, Debug)]
 pub struct ModuleWithCalls {
     index: u8,
-    calls: HashMap<String, u8>,
+    name: String,
+    calls: HashMap<String, ModuleCallMetadata>,
 }
 
 impl ModuleWithCalls {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn call<T: Encode>(
         &self,
-        function: &'static str,
+        function: &str,
         params: T,
     ) -> Result<Encoded, MetadataError> {
-        let fn_index = self
-            .calls
-            .get(function)
-            .ok_or(MetadataError::CallNotFound(function))?;
-        let mut bytes = vec![self.index, *fn_index];
+        let call = self.call_metadata(function)?;
+        let mut bytes = vec![self.index, call.index];
         bytes.extend(params.encode());
         Ok(Encoded(bytes))
     }
+
+    pub fn calls(&self) -> impl Iterator<Item = &ModuleCallMetadata> {
+        self.calls.values()
+    }
+
+    pub fn call_metadata(
+        &self,
+        function: &str,
+    ) -> Result<&ModuleCallMetadata, MetadataError> {
+        self.calls
+            .get(function)
+            .ok_or_else(|| MetadataError::CallNotFound(function.to_string()))
+    }
+
+    pub fn call_by_index(&self, index: u8) -> Result<&ModuleCallMetadata, MetadataError> {
+        self.calls
+            .values()
+            .find(|call| call.index == index)
+            .ok_or(MetadataError::CallIndexNotFound(index))
+    }
+}
+
+/// Metadata of a call.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ModuleCallMetadata {
+    index: u8,
+    name: String,
+    arguments: Vec<CallArg>,
+    documentation: Vec<String>,
+}
+
+impl ModuleCallMetadata {
+    /// Index of the call within its module
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// Name
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// Arguments, in the order they are encoded
+    pub fn arguments(&self) -> &[CallArg] {
+        &self.arguments
+    }
+
+    /// Documentation
+    pub fn documentation(&self) -> &Vec<String> {
+        &self.documentation
+    }
+}
+
+/// Argument of a call.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct CallArg {
+    /// Argument name.
+    pub name: String,
+    /// Type (as defined in the runtime).
+    pub ty: String,
 }
 
 #[derive(Clone, Debug)]
@@ -236,6 +587,16 @@ impl ModuleWithEvents {
             .get(&index)
             .ok_or(MetadataError::EventNotFound(index))
     }
+
+    pub fn event_by_name(
+        &self,
+        name: &str,
+    ) -> Result<&ModuleEventMetadata, MetadataError> {
+        self.events
+            .values()
+            .find(|event| event.name == name)
+            .ok_or_else(|| MetadataError::EventNameNotFound(name.to_string()))
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -262,8 +623,11 @@ pub struct StorageMetadata {
     module_prefix: String,
     storage_prefix: String,
     modifier: StorageEntryModifier,
-    ty: StorageEntryType,
+    hashers: Vec<StorageHasher>,
+    key_tys: Vec<String>,
+    value_ty: String,
     default: Vec<u8>,
+    documentation: Vec<String>,
 }
 
 impl StorageMetadata {
@@ -273,6 +637,36 @@ impl StorageMetadata {
         StorageKey(bytes)
     }
 
+    /// Name
+    pub fn name(&self) -> &str {
+        &self.storage_prefix
+    }
+
+    /// Whether the value is `Optional` or has a `Default`
+    pub fn modifier(&self) -> &StorageEntryModifier {
+        &self.modifier
+    }
+
+    /// Hashers of the keys, empty for plain storage values
+    pub fn hashers(&self) -> &[StorageHasher] {
+        &self.hashers
+    }
+
+    /// Types of the keys (as defined in the runtime), empty for plain storage values
+    pub fn key_tys(&self) -> &[String] {
+        &self.key_tys
+    }
+
+    /// Type of the stored value (as defined in the runtime)
+    pub fn value_ty(&self) -> &str {
+        &self.value_ty
+    }
+
+    /// Documentation
+    pub fn documentation(&self) -> &Vec<String> {
+        &self.documentation
+    }
+
     pub fn default<V: Decode>(&self) -> Result<V, MetadataError> {
         Decode::decode(&mut &self.default[..]).map_err(MetadataError::DefaultError)
     }
@@ -307,8 +701,8 @@ impl StorageMetadata {
     }
 
     pub fn plain(&self) -> Result<StoragePlain, MetadataError> {
-        match &self.ty {
-            StorageEntryType::Plain(_) => {
+        match self.hashers.as_slice() {
+            [] => {
                 Ok(StoragePlain {
                     prefix: self.prefix().0,
                 })
@@ -318,8 +712,8 @@ impl StorageMetadata {
     }
 
     pub fn map<K: Encode>(&self) -> Result<StorageMap<K>, MetadataError> {
-        match &self.ty {
-            StorageEntryType::Map { hasher, .. } => {
+        match self.hashers.as_slice() {
+            [hasher] => {
                 Ok(StorageMap {
                     _marker: PhantomData,
                     prefix: self.prefix().0,
@@ -333,22 +727,32 @@ impl StorageMetadata {
     pub fn double_map<K1: Encode, K2: Encode>(
         &self,
     ) -> Result<StorageDoubleMap<K1, K2>, MetadataError> {
-        match &self.ty {
-            StorageEntryType::DoubleMap {
-                hasher,
-                key2_hasher,
-                ..
-            } => {
+        match self.hashers.as_slice() {
+            [hasher1, hasher2] => {
                 Ok(StorageDoubleMap {
                     _marker: PhantomData,
                     prefix: self.prefix().0,
-                    hasher1: hasher.clone(),
-                    hasher2: key2_hasher.clone(),
+                    hasher1: hasher1.clone(),
+                    hasher2: hasher2.clone(),
                 })
             }
             _ => Err(MetadataError::StorageTypeError),
         }
     }
+
+    /// Returns a key builder for a map with any number of keys, which can be used when the
+    /// key types are only known at runtime.
+    pub fn n_map(&self) -> Result<StorageNMap, MetadataError> {
+        match self.hashers.as_slice() {
+            [] => Err(MetadataError::StorageTypeError),
+            hashers => {
+                Ok(StorageNMap {
+                    prefix: self.prefix().0,
+                    hashers: hashers.to_vec(),
+                })
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -394,6 +798,29 @@ impl<K1: Encode, K2: Encode> StorageDoubleMap<K1, K2> {
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct StorageNMap {
+    prefix: Vec<u8>,
+    hashers: Vec<StorageHasher>,
+}
+
+impl StorageNMap {
+    /// Returns the `StorageKey` for the SCALE encoded `keys`, one key per hasher.
+    pub fn key(&self, keys: &[Vec<u8>]) -> Result<StorageKey, MetadataError> {
+        if keys.len() != self.hashers.len() {
+            return Err(MetadataError::StorageKeysMismatch(
+                self.hashers.len(),
+                keys.len(),
+            ))
+        }
+        let mut bytes = self.prefix.clone();
+        for (hasher, key) in self.hashers.iter().zip(keys) {
+            bytes.extend(StorageMetadata::hash(hasher, key));
+        }
+        Ok(StorageKey(bytes))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ModuleEventMetadata {
     pub name: String,
@@ -461,6 +888,20 @@ impl FromStr for EventArg {
     }
 }
 
+impl fmt::Display for EventArg {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EventArg::Primitive(p) => write!(f, "{}", p),
+            EventArg::Vec(arg) => write!(f, "Vec<{}>", arg),
+            EventArg::Option(arg) => write!(f, "Option<{}>", arg),
+            EventArg::Tuple(args) => {
+                let args = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+                write!(f, "({})", args.join(", "))
+            }
+        }
+    }
+}
+
 impl EventArg {
     /// Returns all primitive types for this EventArg
     pub fn primitives(&self) -> Vec<String> {
@@ -513,14 +954,37 @@ impl ModuleConstantMetadata {
 pub enum ConversionError {
     #[error("Invalid prefix")]
     InvalidPrefix,
-    #[error("Invalid version")]
-    InvalidVersion,
+    #[error("Unsupported metadata version {0}")]
+    InvalidVersion(u32),
+    #[error("Failed to upgrade metadata: {0}")]
+    Upgrade(CodecError),
     #[error("Expected DecodeDifferent::Decoded")]
     ExpectedDecoded,
     #[error("Invalid event arg {0}")]
     InvalidEventArg(String, &'static str),
 }
 
+/// Returns the version of the metadata.
+fn metadata_version(metadata: &RuntimeMetadata) -> u32 {
+    match metadata {
+        RuntimeMetadata::V0(_) => 0,
+        RuntimeMetadata::V1(_) => 1,
+        RuntimeMetadata::V2(_) => 2,
+        RuntimeMetadata::V3(_) => 3,
+        RuntimeMetadata::V4(_) => 4,
+        RuntimeMetadata::V5(_) => 5,
+        RuntimeMetadata::V6(_) => 6,
+        RuntimeMetadata::V7(_) => 7,
+        RuntimeMetadata::V8(_) => 8,
+        RuntimeMetadata::V9(_) => 9,
+        RuntimeMetadata::V10(_) => 10,
+        RuntimeMetadata::V11(_) => 11,
+        RuntimeMetadata::V12(_) => 12,
+        RuntimeMetadata::V13(_) => 13,
+        RuntimeMetadata::V14(_) => 14,
+    }
+}
+
 impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
     type Error = MetadataError;
 
@@ -528,9 +992,16 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
         if metadata.0 != META_RESERVED {
             return Err(ConversionError::InvalidPrefix.into())
         }
+        let encoded = metadata.encode();
         let meta = match metadata.1 {
-            RuntimeMetadata::V12(meta) => meta,
-            _ => return Err(ConversionError::InvalidVersion.into()),
+            RuntimeMetadata::V12(meta) => upgrade_v12(meta)?,
+            RuntimeMetadata::V13(meta) => meta,
+            unsupported => {
+                return Err(ConversionError::InvalidVersion(metadata_version(
+                    &unsupported,
+                ))
+                .into())
+            }
         };
         let mut modules = HashMap::new();
         let mut modules_with_calls = HashMap::new();
@@ -572,13 +1043,14 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
             if let Some(calls) = module.calls {
                 let mut call_map = HashMap::new();
                 for (index, call) in convert(calls)?.into_iter().enumerate() {
-                    let name = convert(call.name)?;
-                    call_map.insert(name, index as u8);
+                    let call = convert_call(index as u8, call)?;
+                    call_map.insert(call.name.clone(), call);
                 }
                 modules_with_calls.insert(
                     module_name.clone(),
                     ModuleWithCalls {
                         index: module.index,
+                        name: module_name.clone(),
                         calls: call_map,
                     },
                 );
@@ -615,10 +1087,17 @@ impl TryFrom<RuntimeMetadataPrefixed> for Metadata {
             modules_with_calls,
             modules_with_events,
             modules_with_errors,
+            encoded,
         })
     }
 }
 
+/// V13 only adds the `NMap` storage entry type, so every V12 encoding is also a valid V13
+/// encoding and can be converted by a decoding round trip.
+fn upgrade_v12(meta: RuntimeMetadataV12) -> Result<RuntimeMetadataV13, ConversionError> {
+    RuntimeMetadataV13::decode(&mut &meta.encode()[..]).map_err(ConversionError::Upgrade)
+}
+
 fn convert<B: 'static, O: 'static>(
     dd: DecodeDifferent<B, O>,
 ) -> Result<O, ConversionError> {
@@ -628,8 +1107,29 @@ fn convert<B: 'static, O: 'static>(
     }
 }
 
+fn convert_call(
+    index: u8,
+    call: frame_metadata::v13::FunctionMetadata,
+) -> Result<ModuleCallMetadata, ConversionError> {
+    let name = convert(call.name)?;
+    let mut arguments = Vec::new();
+    for arg in convert(call.arguments)? {
+        arguments.push(CallArg {
+            name: convert(arg.name)?,
+            ty: convert(arg.ty)?,
+        });
+    }
+    let documentation = convert(call.documentation)?;
+    Ok(ModuleCallMetadata {
+        index,
+        name,
+        arguments,
+        documentation,
+    })
+}
+
 fn convert_event(
-    event: frame_metadata::EventMetadata,
+    event: frame_metadata::v13::EventMetadata,
 ) -> Result<ModuleEventMetadata, ConversionError> {
     let name = convert(event.name)?;
     let mut arguments = Vec::new();
@@ -643,26 +1143,54 @@ fn convert_event(
 fn convert_entry(
     module_prefix: String,
     storage_prefix: String,
-    entry: frame_metadata::StorageEntryMetadata,
+    entry: frame_metadata::v13::StorageEntryMetadata,
 ) -> Result<StorageMetadata, ConversionError> {
+    let (hashers, key_tys, value_ty) = match entry.ty {
+        StorageEntryType::Plain(value) => (vec![], vec![], convert(value)?),
+        StorageEntryType::Map {
+            hasher, key, value, ..
+        } => (vec![hasher], vec![convert(key)?], convert(value)?),
+        StorageEntryType::DoubleMap {
+            hasher,
+            key1,
+            key2,
+            value,
+            key2_hasher,
+        } => {
+            (
+                vec![hasher, key2_hasher],
+                vec![convert(key1)?, convert(key2)?],
+                convert(value)?,
+            )
+        }
+        StorageEntryType::NMap {
+            keys,
+            hashers,
+            value,
+        } => (convert(hashers)?, convert(keys)?, convert(value)?),
+    };
     let default = convert(entry.default)?;
+    let documentation = convert(entry.documentation)?;
     Ok(StorageMetadata {
         module_prefix,
         storage_prefix,
         modifier: entry.modifier,
-        ty: entry.ty,
+        hashers,
+        key_tys,
+        value_ty,
         default,
+        documentation,
     })
 }
 
 fn convert_error(
-    error: frame_metadata::ErrorMetadata,
+    error: frame_metadata::v13::ErrorMetadata,
 ) -> Result<String, ConversionError> {
     convert(error.name)
 }
 
 fn convert_constant(
-    constant: frame_metadata::ModuleConstantMetadata,
+    constant: frame_metadata::v13::ModuleConstantMetadata,
 ) -> Result<ModuleConstantMetadata, ConversionError> {
     let name = convert(constant.name)?;
     let ty = convert(constant.ty)?;
@@ -675,3 +1203,327 @@ fn convert_constant(
         documentation,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        Primitive,
+        Value,
+    };
+    use codec::Compact;
+    use frame_metadata::{
+        v13::{
+            EventMetadata,
+            ExtrinsicMetadata,
+            FunctionArgumentMetadata,
+            FunctionMetadata,
+            ModuleMetadata as ModuleMetadataV13,
+            StorageEntryMetadata,
+            StorageMetadata as StorageMetadataV13,
+        },
+        OpaqueMetadata,
+    };
+
+    fn n_map_metadata() -> RuntimeMetadataPrefixed {
+        RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V13(RuntimeMetadataV13 {
+                modules: DecodeDifferent::Decoded(vec![ModuleMetadataV13 {
+                    name: DecodeDifferent::Decoded("Assets".to_string()),
+                    storage: Some(DecodeDifferent::Decoded(StorageMetadataV13 {
+                        prefix: DecodeDifferent::Decoded("Assets".to_string()),
+                        entries: DecodeDifferent::Decoded(vec![StorageEntryMetadata {
+                            name: DecodeDifferent::Decoded("Approvals".to_string()),
+                            modifier: StorageEntryModifier::Optional,
+                            ty: StorageEntryType::NMap {
+                                keys: DecodeDifferent::Decoded(vec![
+                                    "AssetId".to_string(),
+                                    "AccountId".to_string(),
+                                    "AccountId".to_string(),
+                                ]),
+                                hashers: DecodeDifferent::Decoded(vec![
+                                    StorageHasher::Blake2_128Concat,
+                                    StorageHasher::Blake2_128Concat,
+                                    StorageHasher::Twox64Concat,
+                                ]),
+                                value: DecodeDifferent::Decoded("Approval".to_string()),
+                            },
+                            default: DecodeDifferent::Decoded(vec![0]),
+                            documentation: DecodeDifferent::Decoded(vec![
+                                " Approved transfers.".to_string(),
+                            ]),
+                        }]),
+                    })),
+                    calls: Some(DecodeDifferent::Decoded(vec![
+                        FunctionMetadata {
+                            name: DecodeDifferent::Decoded("create".to_string()),
+                            arguments: DecodeDifferent::Decoded(vec![]),
+                            documentation: DecodeDifferent::Decoded(vec![]),
+                        },
+                        FunctionMetadata {
+                            name: DecodeDifferent::Decoded("transfer".to_string()),
+                            arguments: DecodeDifferent::Decoded(vec![
+                                FunctionArgumentMetadata {
+                                    name: DecodeDifferent::Decoded("id".to_string()),
+                                    ty: DecodeDifferent::Decoded("u32".to_string()),
+                                },
+                                FunctionArgumentMetadata {
+                                    name: DecodeDifferent::Decoded("amount".to_string()),
+                                    ty: DecodeDifferent::Decoded(
+                                        "Compact<T::Balance>".to_string(),
+                                    ),
+                                },
+                            ]),
+                            documentation: DecodeDifferent::Decoded(vec![
+                                " Move some assets.".to_string(),
+                            ]),
+                        },
+                    ])),
+                    event: Some(DecodeDifferent::Decoded(vec![EventMetadata {
+                        name: DecodeDifferent::Decoded("Transferred".to_string()),
+                        arguments: DecodeDifferent::Decoded(vec![
+                            "AssetId".to_string(),
+                            "AccountId".to_string(),
+                            "AccountId".to_string(),
+                            "Option<BalanceOf<T>>".to_string(),
+                        ]),
+                        documentation: DecodeDifferent::Decoded(vec![]),
+                    }])),
+                    constants: DecodeDifferent::Decoded(vec![]),
+                    errors: DecodeDifferent::Decoded(vec![]),
+                    index: 5,
+                }]),
+                extrinsic: ExtrinsicMetadata {
+                    version: 4,
+                    signed_extensions: vec![],
+                },
+            }),
+        )
+    }
+
+    #[test]
+    fn test_convert_v13_n_map() {
+        let metadata = Metadata::try_from(n_map_metadata()).unwrap();
+        let storage = metadata
+            .module("Assets")
+            .unwrap()
+            .storage("Approvals")
+            .unwrap();
+        assert!(storage.double_map::<u32, u32>().is_err());
+        assert_eq!(storage.name(), "Approvals");
+        assert_eq!(storage.modifier(), &StorageEntryModifier::Optional);
+        assert_eq!(storage.key_tys(), &["AssetId", "AccountId", "AccountId"]);
+        assert_eq!(storage.value_ty(), "Approval");
+        assert_eq!(
+            storage.documentation(),
+            &vec![" Approved transfers.".to_string()]
+        );
+
+        let n_map = storage.n_map().unwrap();
+        let keys = vec![1u32.encode(), 2u32.encode(), 3u32.encode()];
+        let mut expected = storage.prefix().0;
+        expected.extend(StorageMetadata::hash_key(
+            &StorageHasher::Blake2_128Concat,
+            &1u32,
+        ));
+        expected.extend(StorageMetadata::hash_key(
+            &StorageHasher::Blake2_128Concat,
+            &2u32,
+        ));
+        expected.extend(StorageMetadata::hash_key(
+            &StorageHasher::Twox64Concat,
+            &3u32,
+        ));
+        assert_eq!(n_map.key(&keys).unwrap(), StorageKey(expected));
+        assert!(matches!(
+            n_map.key(&keys[..2]),
+            Err(MetadataError::StorageKeysMismatch(3, 2))
+        ));
+    }
+
+    #[test]
+    fn test_validate() {
+        let metadata = Metadata::try_from(n_map_metadata()).unwrap();
+        let args = ["u32", "Compact<T::Balance>"];
+        assert!(metadata.validate_call("Assets", "transfer", &args).is_ok());
+        match metadata.validate_call("Assets", "transfer", &args[..1]) {
+            Err(MetadataError::FieldCountMismatch(name, 2, 1)) => {
+                assert_eq!(name, "Assets::transfer")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match metadata.validate_call("Assets", "transfer", &["u32", "T::Balance"]) {
+            Err(MetadataError::TypeMismatch(name, _, got)) => {
+                assert_eq!(name, "Assets::transfer.amount");
+                assert_eq!(got, "T::Balance");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match metadata.validate_call("Assets", "burn", &[]) {
+            Err(MetadataError::CallNotFound(name)) => assert_eq!(name, "burn"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let keys = ["T::AssetId", "&'a <T as System>::AccountId", "T::AccountId"];
+        assert!(metadata
+            .validate_storage("Assets", "Approvals", &keys, "Approval")
+            .is_ok());
+        match metadata.validate_storage("Assets", "Approvals", &keys[..2], "Approval") {
+            Err(MetadataError::FieldCountMismatch(name, 3, 2)) => {
+                assert_eq!(name, "Assets::Approvals")
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let keys = ["u32", "T::AccountId", "T::AccountId"];
+        match metadata.validate_storage("Assets", "Approvals", &keys, "Approval") {
+            Err(MetadataError::TypeMismatch(name, expected, got)) => {
+                assert_eq!(name, "Assets::Approvals.0");
+                assert_eq!(expected, "AssetId");
+                assert_eq!(got, "u32");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let args = [
+            "T::AssetId",
+            "<T as System>::AccountId",
+            "<T as System>::AccountId",
+            "Option<T::Balance>",
+        ];
+        assert!(metadata
+            .validate_event("Assets", "Transferred", &args)
+            .is_ok());
+        let args = ["T::AssetId", "T::AccountId", "T::AccountId", "T::Balance"];
+        match metadata.validate_event("Assets", "Transferred", &args) {
+            Err(MetadataError::TypeMismatch(name, expected, got)) => {
+                assert_eq!(name, "Assets::Transferred.3");
+                assert_eq!(expected, "Option<BalanceOf<T>>");
+                assert_eq!(got, "T::Balance");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_type_names_match() {
+        assert!(type_names_match(
+            "T::AccountId",
+            "< T as System > :: AccountId"
+        ));
+        assert!(type_names_match("AccountId", "&'a T::AccountId"));
+        assert!(type_names_match("BalanceOf<T>", "T::Balance"));
+        assert!(type_names_match(
+            "AccountInfo<T::Index, T::AccountData>",
+            "AccountInfo<T>"
+        ));
+        assert!(type_names_match(
+            "Vec<(T::AccountId, Balance)>",
+            "Vec<(AccountId, BalanceOf<T>)>"
+        ));
+        assert!(type_names_match("Vec<u8>", "Vec<u8>"));
+        assert!(type_names_match("Vec<u8>", "&'a [u8]"));
+        assert!(type_names_match(
+            "Vec<<T::Lookup as StaticLookup>::Source>",
+            "Vec<T::Address>"
+        ));
+        assert!(type_names_match("Compact<Weight>", "Compact<Gas>"));
+        assert!(type_names_match("Box<<T as Config>::Call>", "&'a Encoded"));
+        assert!(type_names_match(
+            "StakingLedger<T::AccountId, BalanceOf<T>>",
+            "StakingLedger<T::AccountId, T::Balance>"
+        ));
+        assert!(!type_names_match("Vec<u8>", "Vec<u32>"));
+        assert!(!type_names_match(
+            "RewardDestination<T::AccountId>",
+            "RewardDestination<T::Balance>"
+        ));
+        assert!(!type_names_match("BalanceOf<T, I>", "T::Balance"));
+        assert!(!type_names_match("Approval", "Approval<T>"));
+        assert!(!type_names_match("Option<AccountId>", "AccountId"));
+        assert!(!type_names_match(
+            "(AccountId, Balance)",
+            "(AccountId, AccountId)"
+        ));
+    }
+
+    #[test]
+    fn test_call_metadata() {
+        let metadata = Metadata::try_from(n_map_metadata()).unwrap();
+        let module = metadata.module_with_calls("Assets").unwrap();
+        let transfer = module.call_metadata("transfer").unwrap();
+        assert_eq!(transfer.index(), 1);
+        assert_eq!(
+            transfer.arguments(),
+            &[
+                CallArg {
+                    name: "id".to_string(),
+                    ty: "u32".to_string(),
+                },
+                CallArg {
+                    name: "amount".to_string(),
+                    ty: "Compact<T::Balance>".to_string(),
+                },
+            ]
+        );
+        assert_eq!(module.call_by_index(1).unwrap(), transfer);
+
+        let decoder = crate::EventsDecoder::<crate::NodeTemplateRuntime>::new(
+            metadata.clone(),
+            crate::EventTypeRegistry::new(),
+        );
+        let call = module.call("transfer", (7u32, Compact(1_000u128))).unwrap();
+        assert_eq!(
+            decoder.decode_call(&mut &call.0[..]).unwrap(),
+            crate::DecodedCall {
+                module: "Assets".to_string(),
+                function: "transfer".to_string(),
+                arguments: vec![
+                    ("id".to_string(), Value::Primitive(Primitive::U32(7))),
+                    (
+                        "amount".to_string(),
+                        Value::Primitive(Primitive::U128(1_000))
+                    ),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_encoded_round_trip() {
+        let metadata = Metadata::try_from(n_map_metadata()).unwrap();
+        assert_eq!(metadata.encoded(), &n_map_metadata().encode()[..]);
+
+        let decoded = Metadata::from_encoded(metadata.encoded()).unwrap();
+        assert_eq!(decoded.encoded(), metadata.encoded());
+        assert!(metadata.diff(&decoded).is_empty());
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        let metadata = RuntimeMetadataPrefixed(
+            META_RESERVED,
+            RuntimeMetadata::V11(OpaqueMetadata(vec![])),
+        );
+        assert!(matches!(
+            Metadata::try_from(metadata),
+            Err(MetadataError::Conversion(ConversionError::InvalidVersion(
+                11
+            )))
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_newer_version() {
+        // V14 is opaque without the `v14` feature of frame-metadata.
+        let mut encoded = META_RESERVED.encode();
+        encoded.push(14);
+        encoded.extend(vec![1u8, 2, 3].encode());
+        assert!(matches!(
+            Metadata::from_encoded(&encoded),
+            Err(MetadataError::Conversion(ConversionError::InvalidVersion(
+                14
+            )))
+        ));
+    }
+}
@@ -0,0 +1,543 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Comparison of two runtime metadata versions.
+
+use std::collections::{
+    BTreeSet,
+    HashMap,
+};
+
+use super::{
+    EventArg,
+    Metadata,
+    ModuleCallMetadata,
+    ModuleConstantMetadata,
+    StorageMetadata,
+};
+
+/// Differences between an old and a new version of the runtime metadata.
+#[derive(Clone, Debug, Default)]
+pub struct MetadataDiff {
+    /// Modules only present in the new metadata.
+    pub added_modules: Vec<String>,
+    /// Modules only present in the old metadata.
+    pub removed_modules: Vec<String>,
+    /// Modules present in both versions which differ.
+    pub changed_modules: Vec<ModuleDiff>,
+}
+
+impl MetadataDiff {
+    /// Returns `true` if both versions are identical.
+    pub fn is_empty(&self) -> bool {
+        self.added_modules.is_empty()
+            && self.removed_modules.is_empty()
+            && self.changed_modules.is_empty()
+    }
+
+    /// Returns `true` if anything was removed or changed, i.e. clients built against the
+    /// old metadata may fail to encode or decode data of the new runtime.
+    pub fn is_breaking(&self) -> bool {
+        !self.removed_modules.is_empty()
+            || self.changed_modules.iter().any(ModuleDiff::is_breaking)
+    }
+}
+
+/// Differences of a module present in both metadata versions.
+#[derive(Clone, Debug)]
+pub struct ModuleDiff {
+    /// Module name.
+    pub name: String,
+    /// Old and new module index, if it changed.
+    pub index: Option<(u8, u8)>,
+    /// Changes of call indices and arguments.
+    pub calls: Vec<ItemChange<ModuleCallMetadata>>,
+    /// Changes of storage entries.
+    pub storage: Vec<ItemChange<StorageMetadata>>,
+    /// Changes of event indices and argument types.
+    pub events: Vec<ItemChange<(u8, Vec<EventArg>)>>,
+    /// Changes of error indices.
+    pub errors: Vec<ItemChange<u8>>,
+    /// Changes of constant types and values.
+    pub constants: Vec<ItemChange<ModuleConstantMetadata>>,
+}
+
+impl ModuleDiff {
+    fn new(name: String) -> Self {
+        Self {
+            name,
+            index: None,
+            calls: Vec::new(),
+            storage: Vec::new(),
+            events: Vec::new(),
+            errors: Vec::new(),
+            constants: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if there are no differences.
+    pub fn is_empty(&self) -> bool {
+        self.index.is_none()
+            && self.calls.is_empty()
+            && self.storage.is_empty()
+            && self.events.is_empty()
+            && self.errors.is_empty()
+            && self.constants.is_empty()
+    }
+
+    /// Returns `true` if the module index changed or any item was removed or changed.
+    pub fn is_breaking(&self) -> bool {
+        self.index.is_some()
+            || self.calls.iter().any(ItemChange::is_breaking)
+            || self.storage.iter().any(ItemChange::is_breaking)
+            || self.events.iter().any(ItemChange::is_breaking)
+            || self.errors.iter().any(ItemChange::is_breaking)
+            || self.constants.iter().any(ItemChange::is_breaking)
+    }
+}
+
+/// Change of a named module item.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ItemChange<T> {
+    /// The item is only present in the new metadata.
+    Added(String),
+    /// The item is only present in the old metadata.
+    Removed(String),
+    /// The item is present in both versions but differs.
+    Changed {
+        /// Item name.
+        name: String,
+        /// The old item.
+        old: T,
+        /// The new item.
+        new: T,
+    },
+}
+
+impl<T> ItemChange<T> {
+    /// Item name.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Added(name) | Self::Removed(name) => name,
+            Self::Changed { name, .. } => name,
+        }
+    }
+
+    /// Returns `true` unless the item was added.
+    pub fn is_breaking(&self) -> bool {
+        !matches!(self, Self::Added(_))
+    }
+}
+
+impl Metadata {
+    /// Compares this (old) metadata with a `new` version.
+    pub fn diff(&self, new: &Metadata) -> MetadataDiff {
+        let mut diff = MetadataDiff::default();
+        let names = self
+            .modules
+            .keys()
+            .chain(new.modules.keys())
+            .collect::<BTreeSet<_>>();
+        for name in names {
+            let (old_module, new_module) =
+                match (self.modules.get(name), new.modules.get(name)) {
+                    (Some(old_module), Some(new_module)) => (old_module, new_module),
+                    (Some(_), None) => {
+                        diff.removed_modules.push(name.clone());
+                        continue
+                    }
+                    (None, _) => {
+                        diff.added_modules.push(name.clone());
+                        continue
+                    }
+                };
+            let mut module = ModuleDiff::new(name.clone());
+            if old_module.index != new_module.index {
+                module.index = Some((old_module.index, new_module.index));
+            }
+            module.calls =
+                diff_items(&self.calls_of(name), &new.calls_of(name), |old, new| {
+                    old.index != new.index || old.arguments != new.arguments
+                });
+            module.storage =
+                diff_items(&old_module.storage, &new_module.storage, |old, new| {
+                    old.modifier != new.modifier
+                        || old.hashers != new.hashers
+                        || old.key_tys != new.key_tys
+                        || old.value_ty != new.value_ty
+                        || old.default != new.default
+                });
+            module.events = diff_items(
+                &self.event_signatures(name),
+                &new.event_signatures(name),
+                |old, new| old != new,
+            );
+            module.errors = diff_items(
+                &self.error_indices(name),
+                &new.error_indices(name),
+                |old, new| old != new,
+            );
+            module.constants =
+                diff_items(&old_module.constants, &new_module.constants, |old, new| {
+                    old.ty != new.ty || old.value != new.value
+                });
+            if !module.is_empty() {
+                diff.changed_modules.push(module);
+            }
+        }
+        diff
+    }
+
+    fn calls_of(&self, module: &str) -> HashMap<String, ModuleCallMetadata> {
+        self.modules_with_calls
+            .get(module)
+            .map(|module| module.calls.clone())
+            .unwrap_or_default()
+    }
+
+    fn event_signatures(&self, module: &str) -> HashMap<String, (u8, Vec<EventArg>)> {
+        self.modules_with_events
+            .get(module)
+            .map(|module| {
+                module
+                    .events
+                    .iter()
+                    .map(|(index, event)| {
+                        (event.name.clone(), (*index, event.arguments.clone()))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn error_indices(&self, module: &str) -> HashMap<String, u8> {
+        self.modules_with_errors
+            .get(module)
+            .map(|module| {
+                module
+                    .errors
+                    .iter()
+                    .map(|(index, name)| (name.clone(), *index))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+fn diff_items<T, F>(
+    old: &HashMap<String, T>,
+    new: &HashMap<String, T>,
+    changed: F,
+) -> Vec<ItemChange<T>>
+where
+    T: Clone,
+    F: Fn(&T, &T) -> bool,
+{
+    let names = old.keys().chain(new.keys()).collect::<BTreeSet<_>>();
+    names
+        .into_iter()
+        .filter_map(|name| {
+            match (old.get(name), new.get(name)) {
+                (Some(old), Some(new)) if changed(old, new) => {
+                    Some(ItemChange::Changed {
+                        name: name.clone(),
+                        old: old.clone(),
+                        new: new.clone(),
+                    })
+                }
+                (Some(_), Some(_)) => None,
+                (Some(_), None) => Some(ItemChange::Removed(name.clone())),
+                (None, _) => Some(ItemChange::Added(name.clone())),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata::{
+        ModuleEventMetadata,
+        ModuleMetadata,
+        ModuleWithCalls,
+        ModuleWithErrors,
+        ModuleWithEvents,
+    };
+    use frame_metadata::v13::{
+        StorageEntryModifier,
+        StorageHasher,
+    };
+
+    fn call(name: &str, index: u8) -> ModuleCallMetadata {
+        ModuleCallMetadata {
+            index,
+            name: name.to_string(),
+            arguments: Vec::new(),
+            documentation: Vec::new(),
+        }
+    }
+
+    fn metadata(modules: Vec<(&str, u8, Vec<(&str, u8)>)>) -> Metadata {
+        let mut metadata = Metadata::default();
+        for (name, index, calls) in modules {
+            metadata.modules.insert(
+                name.to_string(),
+                ModuleMetadata {
+                    index,
+                    name: name.to_string(),
+                    storage: HashMap::new(),
+                    constants: HashMap::new(),
+                },
+            );
+            metadata.modules_with_calls.insert(
+                name.to_string(),
+                ModuleWithCalls {
+                    index,
+                    name: name.to_string(),
+                    calls: calls
+                        .into_iter()
+                        .map(|(name, index)| (name.to_string(), call(name, index)))
+                        .collect(),
+                },
+            );
+        }
+        metadata
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = metadata(vec![
+            ("System", 0, vec![("remark", 0)]),
+            ("Balances", 1, vec![("transfer", 0), ("set_balance", 1)]),
+            ("Sudo", 2, vec![]),
+        ]);
+        let new = metadata(vec![
+            ("System", 0, vec![("remark", 0)]),
+            (
+                "Balances",
+                2,
+                vec![("transfer", 0), ("set_balance", 2), ("transfer_all", 1)],
+            ),
+            ("Assets", 3, vec![]),
+        ]);
+
+        assert!(old.diff(&old).is_empty());
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_modules, vec!["Assets".to_string()]);
+        assert_eq!(diff.removed_modules, vec!["Sudo".to_string()]);
+        assert_eq!(diff.changed_modules.len(), 1);
+        let balances = &diff.changed_modules[0];
+        assert_eq!(balances.name, "Balances");
+        assert_eq!(balances.index, Some((1, 2)));
+        assert_eq!(
+            balances.calls,
+            vec![
+                ItemChange::Changed {
+                    name: "set_balance".to_string(),
+                    old: call("set_balance", 1),
+                    new: call("set_balance", 2),
+                },
+                ItemChange::Added("transfer_all".to_string()),
+            ]
+        );
+        assert!(diff.is_breaking());
+        let old = metadata(vec![("System", 0, vec![("remark", 0)])]);
+        let new = metadata(vec![("System", 0, vec![("remark", 0), ("set_code", 1)])]);
+        assert!(!old.diff(&new).is_breaking());
+    }
+
+    fn storage(name: &str, value_ty: &str) -> StorageMetadata {
+        StorageMetadata {
+            module_prefix: "Balances".to_string(),
+            storage_prefix: name.to_string(),
+            modifier: StorageEntryModifier::Default,
+            hashers: Vec::new(),
+            key_tys: Vec::new(),
+            value_ty: value_ty.to_string(),
+            default: Vec::new(),
+            documentation: Vec::new(),
+        }
+    }
+
+    fn constant(name: &str, value: u128) -> ModuleConstantMetadata {
+        ModuleConstantMetadata {
+            name: name.to_string(),
+            ty: "Balance".to_string(),
+            value: codec::Encode::encode(&value),
+            documentation: Vec::new(),
+        }
+    }
+
+    fn args(args: &[&str]) -> Vec<EventArg> {
+        args.iter().map(|arg| arg.parse().unwrap()).collect()
+    }
+
+    struct Items<'a> {
+        storage: Vec<(&'a str, &'a str)>,
+        events: Vec<(&'a str, u8, Vec<&'a str>)>,
+        errors: Vec<(&'a str, u8)>,
+        constants: Vec<(&'a str, u128)>,
+    }
+
+    fn module_items(items: Items) -> Metadata {
+        let name = "Balances".to_string();
+        let mut metadata = Metadata::default();
+        metadata.modules.insert(
+            name.clone(),
+            ModuleMetadata {
+                index: 0,
+                name: name.clone(),
+                storage: items
+                    .storage
+                    .into_iter()
+                    .map(|(name, ty)| (name.to_string(), storage(name, ty)))
+                    .collect(),
+                constants: items
+                    .constants
+                    .into_iter()
+                    .map(|(name, value)| (name.to_string(), constant(name, value)))
+                    .collect(),
+            },
+        );
+        metadata.modules_with_events.insert(
+            name.clone(),
+            ModuleWithEvents {
+                index: 0,
+                name: name.clone(),
+                events: items
+                    .events
+                    .into_iter()
+                    .map(|(name, index, arguments)| {
+                        let event = ModuleEventMetadata {
+                            name: name.to_string(),
+                            arguments: args(&arguments),
+                        };
+                        (index, event)
+                    })
+                    .collect(),
+            },
+        );
+        metadata.modules_with_errors.insert(
+            name.clone(),
+            ModuleWithErrors {
+                index: 0,
+                name,
+                errors: items
+                    .errors
+                    .into_iter()
+                    .map(|(name, index)| (index, name.to_string()))
+                    .collect(),
+            },
+        );
+        metadata
+    }
+
+    #[test]
+    fn test_diff_module_items() {
+        let old = module_items(Items {
+            storage: vec![
+                ("TotalIssuance", "T::Balance"),
+                ("Locks", "Vec<BalanceLock<T::Balance>>"),
+            ],
+            events: vec![
+                ("Transfer", 0, vec!["AccountId", "AccountId", "Balance"]),
+                ("Deposit", 1, vec!["AccountId", "Balance"]),
+            ],
+            errors: vec![("InsufficientBalance", 0), ("VestingBalance", 1)],
+            constants: vec![("ExistentialDeposit", 1), ("MaxReserves", 50)],
+        });
+        let new = module_items(Items {
+            storage: vec![
+                ("TotalIssuance", "u128"),
+                ("Reserves", "Vec<ReserveData<T::Balance>>"),
+            ],
+            events: vec![
+                ("Transfer", 0, vec!["AccountId", "AccountId", "u128"]),
+                ("Reserved", 1, vec!["AccountId", "Balance"]),
+            ],
+            errors: vec![("VestingBalance", 0), ("LiquidityRestrictions", 1)],
+            constants: vec![("ExistentialDeposit", 10), ("MaxLocks", 50)],
+        });
+
+        assert!(old.diff(&old).is_empty());
+
+        let diff = old.diff(&new);
+        assert!(diff.added_modules.is_empty());
+        assert!(diff.removed_modules.is_empty());
+        assert_eq!(diff.changed_modules.len(), 1);
+        let balances = &diff.changed_modules[0];
+        assert_eq!(balances.index, None);
+        assert!(balances.calls.is_empty());
+
+        let storage = &balances.storage;
+        assert_eq!(storage.len(), 3);
+        assert!(matches!(&storage[0], ItemChange::Removed(name) if name == "Locks"));
+        assert!(matches!(&storage[1], ItemChange::Added(name) if name == "Reserves"));
+        match &storage[2] {
+            ItemChange::Changed { name, old, new } => {
+                assert_eq!(name, "TotalIssuance");
+                assert_eq!(old.value_ty, "T::Balance");
+                assert_eq!(new.value_ty, "u128");
+            }
+            change => panic!("unexpected storage change: {:?}", change),
+        }
+
+        assert_eq!(
+            balances.events,
+            vec![
+                ItemChange::Removed("Deposit".to_string()),
+                ItemChange::Added("Reserved".to_string()),
+                ItemChange::Changed {
+                    name: "Transfer".to_string(),
+                    old: (0, args(&["AccountId", "AccountId", "Balance"])),
+                    new: (0, args(&["AccountId", "AccountId", "u128"])),
+                },
+            ]
+        );
+
+        assert_eq!(
+            balances.errors,
+            vec![
+                ItemChange::Removed("InsufficientBalance".to_string()),
+                ItemChange::Added("LiquidityRestrictions".to_string()),
+                ItemChange::Changed {
+                    name: "VestingBalance".to_string(),
+                    old: 1,
+                    new: 0,
+                },
+            ]
+        );
+
+        let constants = &balances.constants;
+        assert_eq!(constants.len(), 3);
+        match &constants[0] {
+            ItemChange::Changed { name, old, new } => {
+                assert_eq!(name, "ExistentialDeposit");
+                assert_eq!(old.value::<u128>().unwrap(), 1);
+                assert_eq!(new.value::<u128>().unwrap(), 10);
+            }
+            change => panic!("unexpected constant change: {:?}", change),
+        }
+        assert!(matches!(&constants[1], ItemChange::Added(name) if name == "MaxLocks"));
+        assert!(
+            matches!(&constants[2], ItemChange::Removed(name) if name == "MaxReserves")
+        );
+
+        assert!(balances.is_breaking());
+        assert!(diff.is_breaking());
+    }
+}
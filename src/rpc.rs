@@ -19,7 +19,14 @@
 // Related: https://github.com/paritytech/substrate-subxt/issues/66
 #![allow(irrefutable_let_patterns)]
 
-use std::sync::Arc;
+use std::{
+    collections::HashMap,
+    sync::{
+        Arc,
+        RwLock,
+    },
+    time::Duration,
+};
 
 use codec::{
     Decode,
@@ -28,22 +35,23 @@ use codec::{
 };
 use core::{
     convert::TryInto,
+    future::Future,
     marker::PhantomData,
 };
 use frame_metadata::RuntimeMetadataPrefixed;
-use jsonrpsee_http_client::HttpClient;
+use futures::future::{
+    self,
+    Either,
+};
+use futures_timer::Delay;
+use jsonrpsee_http_client::HttpClientBuilder;
 use jsonrpsee_types::{
     to_json_value,
-    traits::{
-        Client,
-        SubscriptionClient,
-    },
     DeserializeOwned,
     Error as RpcError,
     JsonValue,
-    Subscription,
 };
-use jsonrpsee_ws_client::WsClient;
+use jsonrpsee_ws_client::WsClientBuilder;
 use serde::{
     Deserialize,
     Serialize,
@@ -69,6 +77,23 @@ use sp_runtime::{
 };
 use sp_version::RuntimeVersion;
 
+#[cfg(unix)]
+pub use self::ipc::IpcClient;
+pub use self::{
+    failover::{
+        FailoverClient,
+        FailoverClientBuilder,
+    },
+    replay::{
+        RecordingTransport,
+        ReplayTransport,
+        TraceEntry,
+    },
+    transport::{
+        RpcTransport,
+        Subscription,
+    },
+};
 use crate::{
     error::Error,
     events::{
@@ -89,6 +114,12 @@ use crate::{
     },
 };
 
+mod failover;
+#[cfg(unix)]
+mod ipc;
+mod replay;
+mod transport;
+
 pub type ChainBlock<T> =
     SignedBlock<Block<<T as System>::Header, <T as System>::Extrinsic>>;
 
@@ -120,6 +151,23 @@ pub struct SystemProperties {
     pub token_symbol: String,
 }
 
+/// Health struct returned by the RPC
+///
+/// # Note
+///
+/// This is copied from `sc-rpc-api` to avoid a dependency on that crate. Therefore it
+/// must be kept compatible with that type from the target substrate version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Health {
+    /// Number of connected peers
+    pub peers: usize,
+    /// Is the node syncing
+    pub is_syncing: bool,
+    /// Should this node have any peers
+    pub should_have_peers: bool,
+}
+
 /// Possible transaction status events.
 ///
 /// # Note
@@ -153,91 +201,169 @@ pub enum TransactionStatus<Hash, BlockHash> {
     Invalid,
 }
 
-#[cfg(feature = "client")]
-use substrate_subxt_client::SubxtClient;
-
-/// Rpc client wrapper.
-/// This is workaround because adding generic types causes the macros to fail.
+/// Rpc client wrapper around a [`RpcTransport`].
 #[derive(Clone)]
-pub enum RpcClient {
-    /// JSONRPC client WebSocket transport.
-    WebSocket(Arc<WsClient>),
-    /// JSONRPC client HTTP transport.
-    // NOTE: Arc because `HttpClient` is not clone.
-    Http(Arc<HttpClient>),
-    #[cfg(feature = "client")]
-    /// Embedded substrate node.
-    Subxt(SubxtClient),
-}
+pub struct RpcClient(Arc<dyn RpcTransport>);
 
 impl RpcClient {
+    /// Creates a new client sending requests over the given transport.
+    pub fn new<C: RpcTransport + 'static>(transport: C) -> Self {
+        Self(Arc::new(transport))
+    }
+
+    /// Connects to a node, using the WebSocket transport for `ws://` and `wss://` urls, the
+    /// IPC transport for `ipc://` urls and paths, and the HTTP transport otherwise.
+    ///
+    /// [`RpcClient::reconnect`] connects to the url again.
+    pub async fn connect(url: &str) -> Result<Self, Error> {
+        let transport = UrlTransport {
+            url: url.to_string(),
+            transport: RwLock::new(connect_transport(url).await?),
+        };
+        Ok(RpcClient::new(transport))
+    }
+
+    /// Reconnects after the connection was lost, see [`RpcTransport::reconnect`].
+    ///
+    /// Clones of the client, e.g. the one of the `Client`, use the new connection too.
+    pub async fn reconnect(&self) -> Result<(), Error> {
+        self.0.reconnect().await
+    }
+
+    /// Returns `true` if the transport supports reconnecting, see
+    /// [`RpcTransport::can_reconnect`].
+    pub fn can_reconnect(&self) -> bool {
+        self.0.can_reconnect()
+    }
+
     /// Start a JSON-RPC request.
-    pub async fn request<'a, T: DeserializeOwned + std::fmt::Debug>(
+    pub async fn request<T: DeserializeOwned>(
         &self,
         method: &str,
         params: &[JsonValue],
     ) -> Result<T, Error> {
-        let params = params.into();
-        let data = match self {
-            Self::WebSocket(inner) => {
-                inner.request(method, params).await.map_err(Into::into)
-            }
-            Self::Http(inner) => inner.request(method, params).await.map_err(Into::into),
-            #[cfg(feature = "client")]
-            Self::Subxt(inner) => inner.request(method, params).await.map_err(Into::into),
-        };
+        let data = self.0.request(method, params).await;
         log::debug!("{}: {:?}", method, data);
-        data
+        Ok(serde_json::from_value(data?)?)
+    }
+
+    /// Start a JSON-RPC batch request, returning the results in the order of the requests.
+    pub async fn batch_request<T: DeserializeOwned>(
+        &self,
+        batch: &[(&str, &[JsonValue])],
+    ) -> Result<Vec<T>, Error> {
+        let data = self.0.batch_request(batch).await;
+        log::debug!("batch of {} requests: {:?}", batch.len(), data);
+        data?
+            .into_iter()
+            .map(|data| Ok(serde_json::from_value(data)?))
+            .collect()
     }
 
     /// Start a JSON-RPC Subscription.
-    pub async fn subscribe<'a, T: DeserializeOwned>(
+    pub async fn subscribe<T: DeserializeOwned>(
         &self,
         subscribe_method: &str,
         params: &[JsonValue],
         unsubscribe_method: &str,
     ) -> Result<Subscription<T>, Error> {
-        let params = params.into();
-        match self {
-            Self::WebSocket(inner) => {
-                inner
-                    .subscribe(subscribe_method, params, unsubscribe_method)
-                    .await
-                    .map_err(Into::into)
-            }
-            Self::Http(_) => {
-                Err(RpcError::Custom(
-                    "Subscriptions not supported on HTTP transport".to_owned(),
-                )
-                .into())
-            }
-            #[cfg(feature = "client")]
-            Self::Subxt(inner) => {
-                inner
-                    .subscribe(subscribe_method, params, unsubscribe_method)
-                    .await
-                    .map_err(Into::into)
-            }
+        let subscription = self
+            .0
+            .subscribe(subscribe_method, params, unsubscribe_method)
+            .await?;
+        Ok(subscription.cast())
+    }
+}
+
+/// Connects to the node at `url`, see [`RpcClient::connect`].
+async fn connect_transport(url: &str) -> Result<Arc<dyn RpcTransport>, Error> {
+    if let Some(path) = ipc_path(url) {
+        #[cfg(unix)]
+        {
+            return Ok(Arc::new(IpcClient::connect(path)?))
+        }
+        #[cfg(not(unix))]
+        {
+            return Err(format!("IPC transport not supported for {}", path).into())
         }
     }
+    if url.starts_with("ws://") || url.starts_with("wss://") {
+        let client = WsClientBuilder::default()
+            .max_notifs_per_subscription(4096)
+            .build(url)
+            .await?;
+        Ok(Arc::new(client))
+    } else {
+        let client = HttpClientBuilder::default().build(url)?;
+        Ok(Arc::new(client))
+    }
+}
+
+/// Transport connected to a url, reconnecting by connecting to the url again.
+struct UrlTransport {
+    url: String,
+    transport: RwLock<Arc<dyn RpcTransport>>,
+}
+
+impl UrlTransport {
+    fn transport(&self) -> Arc<dyn RpcTransport> {
+        self.transport.read().expect("lock poisoned; qed").clone()
+    }
 }
 
-impl From<WsClient> for RpcClient {
-    fn from(client: WsClient) -> Self {
-        RpcClient::WebSocket(Arc::new(client))
+#[async_trait::async_trait]
+impl RpcTransport for UrlTransport {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        self.transport().request(method, params).await
+    }
+
+    async fn batch_request(
+        &self,
+        batch: &[(&str, &[JsonValue])],
+    ) -> Result<Vec<JsonValue>, Error> {
+        self.transport().batch_request(batch).await
+    }
+
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        self.transport()
+            .subscribe(subscribe_method, params, unsubscribe_method)
+            .await
+    }
+
+    async fn reconnect(&self) -> Result<(), Error> {
+        let transport = connect_transport(&self.url).await?;
+        *self.transport.write().expect("lock poisoned; qed") = transport;
+        Ok(())
+    }
+
+    fn can_reconnect(&self) -> bool {
+        true
     }
 }
 
-impl From<HttpClient> for RpcClient {
-    fn from(client: HttpClient) -> Self {
-        RpcClient::Http(Arc::new(client))
+/// Returns the socket path of `ipc://` urls and plain paths.
+fn ipc_path(url: &str) -> Option<&str> {
+    if let Some(path) = url.strip_prefix("ipc://") {
+        Some(path)
+    } else if url.starts_with('/') || url.starts_with("./") {
+        Some(url)
+    } else {
+        None
     }
 }
 
-#[cfg(feature = "client")]
-impl From<SubxtClient> for RpcClient {
-    fn from(client: SubxtClient) -> Self {
-        RpcClient::Subxt(client)
+impl<C: RpcTransport + 'static> From<C> for RpcClient {
+    fn from(transport: C) -> Self {
+        RpcClient::new(transport)
     }
 }
 
@@ -256,12 +382,84 @@ pub struct ReadProof<Hash> {
     pub proof: Vec<Bytes>,
 }
 
+/// Policy for retrying failed requests.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry.
+    pub initial_backoff: Duration,
+    /// Factor the delay is multiplied by after each retry.
+    pub backoff_multiplier: u32,
+    /// Upper bound for the delay between retries.
+    pub max_backoff: Duration,
+    /// Returns whether a failed request should be retried.
+    pub retryable: fn(&Error) -> bool,
+}
+
+impl Default for RetryPolicy {
+    /// A single attempt, without retries.
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            initial_backoff: Duration::from_millis(100),
+            backoff_multiplier: 2,
+            max_backoff: Duration::from_secs(10),
+            retryable: RetryPolicy::is_transient,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retries up to `max_attempts` times with exponential backoff.
+    pub fn exponential(max_attempts: u32) -> Self {
+        Self {
+            max_attempts,
+            ..Default::default()
+        }
+    }
+
+    /// Returns `true` for transport errors, timeouts and closed connections, which are
+    /// likely to succeed when retried.
+    pub fn is_transient(error: &Error) -> bool {
+        matches!(
+            error,
+            Error::Timeout(_)
+                | Error::Rpc(RpcError::Transport(_))
+                | Error::Rpc(RpcError::RequestTimeout)
+                | Error::Rpc(RpcError::RestartNeeded(_))
+        )
+    }
+
+    /// Returns the delay before the `retry`th retry.
+    fn backoff(&self, retry: u32) -> Duration {
+        let multiplier = self.backoff_multiplier.saturating_pow(retry - 1);
+        self.initial_backoff
+            .checked_mul(multiplier)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+/// Methods which have side effects on the node, and are never retried.
+const NON_IDEMPOTENT_METHODS: &[&str] = &[
+    "author_submitExtrinsic",
+    "author_submitAndWatchExtrinsic",
+    "author_insertKey",
+    "author_rotateKeys",
+];
+
+/// Maximum number of requests sent in a single JSON-RPC batch.
+const MAX_BATCH_SIZE: usize = 512;
+
 /// Client for substrate rpc interfaces
 pub struct Rpc<T: Runtime> {
     /// Rpc client for sending requests.
     pub client: RpcClient,
     marker: PhantomData<T>,
     accept_weak_inclusion: bool,
+    retry_policy: RetryPolicy,
+    request_timeout: Option<Duration>,
+    method_timeouts: HashMap<String, Duration>,
 }
 
 impl<T: Runtime> Clone for Rpc<T> {
@@ -270,6 +468,9 @@ impl<T: Runtime> Clone for Rpc<T> {
             client: self.client.clone(),
             marker: PhantomData,
             accept_weak_inclusion: self.accept_weak_inclusion,
+            retry_policy: self.retry_policy.clone(),
+            request_timeout: self.request_timeout,
+            method_timeouts: self.method_timeouts.clone(),
         }
     }
 }
@@ -280,6 +481,9 @@ impl<T: Runtime> Rpc<T> {
             client,
             marker: PhantomData,
             accept_weak_inclusion: false,
+            retry_policy: Default::default(),
+            request_timeout: None,
+            method_timeouts: HashMap::new(),
         }
     }
 
@@ -289,6 +493,104 @@ impl<T: Runtime> Rpc<T> {
         self.accept_weak_inclusion = true;
     }
 
+    /// Configure the policy for retrying failed requests.
+    pub fn set_retry_policy(&mut self, retry_policy: RetryPolicy) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Configure the timeout of requests without a method specific timeout.
+    pub fn set_request_timeout(&mut self, timeout: Duration) {
+        self.request_timeout = Some(timeout);
+    }
+
+    /// Configure the timeout of requests of the given method.
+    pub fn set_method_timeout(&mut self, method: String, timeout: Duration) {
+        self.method_timeouts.insert(method, timeout);
+    }
+
+    /// Start a JSON-RPC request, applying the configured timeouts and retry policy.
+    async fn request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<R, Error> {
+        self.with_policy(method, || self.client.request(method, params))
+            .await
+    }
+
+    /// Start a JSON-RPC batch request calling `method` once for each entry of `params`,
+    /// applying the configured timeouts and retry policy.
+    ///
+    /// The batch is split into batches of at most `MAX_BATCH_SIZE` requests.
+    async fn batch_request<R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: &[Vec<JsonValue>],
+    ) -> Result<Vec<R>, Error> {
+        let mut results = Vec::with_capacity(params.len());
+        for chunk in params.chunks(MAX_BATCH_SIZE) {
+            let batch = chunk
+                .iter()
+                .map(|params| (method, params.as_slice()))
+                .collect::<Vec<_>>();
+            let chunk_results = self
+                .with_policy(method, || self.client.batch_request(&batch))
+                .await?;
+            results.extend(chunk_results);
+        }
+        Ok(results)
+    }
+
+    /// Runs the request returned by `request` until it succeeds, it fails with an error
+    /// which isn't retryable or the maximum number of attempts is reached.
+    async fn with_policy<R, F, Fut>(&self, method: &str, request: F) -> Result<R, Error>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<R, Error>>,
+    {
+        let timeout = self
+            .method_timeouts
+            .get(method)
+            .copied()
+            .or(self.request_timeout);
+        let max_attempts = if NON_IDEMPOTENT_METHODS.contains(&method) {
+            1
+        } else {
+            self.retry_policy.max_attempts
+        };
+        let mut attempt = 1;
+        loop {
+            let result = match timeout {
+                Some(timeout) => {
+                    let request = request();
+                    futures::pin_mut!(request);
+                    match future::select(request, Delay::new(timeout)).await {
+                        Either::Left((result, _)) => result,
+                        Either::Right(_) => Err(Error::Timeout(method.to_string())),
+                    }
+                }
+                None => request().await,
+            };
+            match result {
+                Err(err)
+                    if attempt < max_attempts && (self.retry_policy.retryable)(&err) =>
+                {
+                    let backoff = self.retry_policy.backoff(attempt);
+                    log::warn!(
+                        "{} failed (attempt {}), retrying in {:?}: {}",
+                        method,
+                        attempt,
+                        backoff,
+                        err
+                    );
+                    Delay::new(backoff).await;
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+
     /// Fetch a storage key
     pub async fn storage(
         &self,
@@ -296,10 +598,40 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<Option<StorageData>, Error> {
         let params = &[to_json_value(key)?, to_json_value(hash)?];
-        let data = self.client.request("state_getStorage", params).await?;
+        let data = self.request("state_getStorage", params).await?;
         Ok(data)
     }
 
+    /// Fetch many storage keys in JSON-RPC batches, returning the values in the order of
+    /// the keys.
+    pub async fn storage_many(
+        &self,
+        keys: &[StorageKey],
+        hash: Option<T::Hash>,
+    ) -> Result<Vec<Option<StorageData>>, Error> {
+        let hash = to_json_value(hash)?;
+        let params = keys
+            .iter()
+            .map(|key| Ok(vec![to_json_value(key)?, hash.clone()]))
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.batch_request("state_getStorage", &params).await
+    }
+
+    /// Fetch a storage key at many blocks in JSON-RPC batches, returning the values in the
+    /// order of the block hashes.
+    pub async fn storage_at_blocks(
+        &self,
+        key: &StorageKey,
+        hashes: &[T::Hash],
+    ) -> Result<Vec<Option<StorageData>>, Error> {
+        let key = to_json_value(key)?;
+        let params = hashes
+            .iter()
+            .map(|hash| Ok(vec![key.clone(), to_json_value(hash)?]))
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.batch_request("state_getStorage", &params).await
+    }
+
     /// Returns the keys with prefix with pagination support.
     /// Up to `count` keys will be returned.
     /// If `start_key` is passed, return next keys in storage in lexicographic order.
@@ -316,7 +648,7 @@ impl<T: Runtime> Rpc<T> {
             to_json_value(start_key)?,
             to_json_value(hash)?,
         ];
-        let data = self.client.request("state_getKeysPaged", params).await?;
+        let data = self.request("state_getKeysPaged", params).await?;
         Ok(data)
     }
 
@@ -332,10 +664,7 @@ impl<T: Runtime> Rpc<T> {
             to_json_value(from)?,
             to_json_value(to)?,
         ];
-        self.client
-            .request("state_queryStorage", params)
-            .await
-            .map_err(Into::into)
+        self.request("state_queryStorage", params).await
     }
 
     /// Query historical storage entries
@@ -345,10 +674,7 @@ impl<T: Runtime> Rpc<T> {
         at: Option<T::Hash>,
     ) -> Result<Vec<StorageChangeSet<<T as System>::Hash>>, Error> {
         let params = &[to_json_value(keys)?, to_json_value(at)?];
-        self.client
-            .request("state_queryStorageAt", params)
-            .await
-            .map_err(Into::into)
+        self.request("state_queryStorageAt", params).await
     }
 
     /// Fetch the genesis hash
@@ -356,7 +682,7 @@ impl<T: Runtime> Rpc<T> {
         let block_zero = Some(ListOrValue::Value(NumberOrHex::Number(0)));
         let params = &[to_json_value(block_zero)?];
         let list_or_value: ListOrValue<Option<T::Hash>> =
-            self.client.request("chain_getBlockHash", params).await?;
+            self.request("chain_getBlockHash", params).await?;
         match list_or_value {
             ListOrValue::Value(genesis_hash) => {
                 genesis_hash.ok_or_else(|| "Genesis hash not found".into())
@@ -367,7 +693,7 @@ impl<T: Runtime> Rpc<T> {
 
     /// Fetch the metadata
     pub async fn metadata(&self) -> Result<Metadata, Error> {
-        let bytes: Bytes = self.client.request("state_getMetadata", &[]).await?;
+        let bytes: Bytes = self.request("state_getMetadata", &[]).await?;
         let meta: RuntimeMetadataPrefixed = Decode::decode(&mut &bytes[..])?;
         let metadata: Metadata = meta.try_into()?;
         Ok(metadata)
@@ -375,7 +701,12 @@ impl<T: Runtime> Rpc<T> {
 
     /// Fetch system properties
     pub async fn system_properties(&self) -> Result<SystemProperties, Error> {
-        Ok(self.client.request("system_properties", &[]).await?)
+        self.request("system_properties", &[]).await
+    }
+
+    /// Fetch system health
+    pub async fn system_health(&self) -> Result<Health, Error> {
+        self.request("system_health", &[]).await
     }
 
     /// Get a header
@@ -384,10 +715,23 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<Option<T::Header>, Error> {
         let params = &[to_json_value(hash)?];
-        let header = self.client.request("chain_getHeader", params).await?;
+        let header = self.request("chain_getHeader", params).await?;
         Ok(header)
     }
 
+    /// Get many block headers in JSON-RPC batches, returning the headers in the order of
+    /// the hashes.
+    pub async fn headers(
+        &self,
+        hashes: &[T::Hash],
+    ) -> Result<Vec<Option<T::Header>>, Error> {
+        let params = hashes
+            .iter()
+            .map(|hash| Ok(vec![to_json_value(hash)?]))
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.batch_request("chain_getHeader", &params).await
+    }
+
     /// Get a block hash, returns hash of latest block by default
     pub async fn block_hash(
         &self,
@@ -395,16 +739,29 @@ impl<T: Runtime> Rpc<T> {
     ) -> Result<Option<T::Hash>, Error> {
         let block_number = block_number.map(ListOrValue::Value);
         let params = &[to_json_value(block_number)?];
-        let list_or_value = self.client.request("chain_getBlockHash", params).await?;
+        let list_or_value = self.request("chain_getBlockHash", params).await?;
         match list_or_value {
             ListOrValue::Value(hash) => Ok(hash),
             ListOrValue::List(_) => Err("Expected a Value, got a List".into()),
         }
     }
 
+    /// Get many block hashes in JSON-RPC batches, returning the hashes in the order of the
+    /// block numbers.
+    pub async fn block_hashes(
+        &self,
+        block_numbers: &[BlockNumber],
+    ) -> Result<Vec<Option<T::Hash>>, Error> {
+        let params = block_numbers
+            .iter()
+            .map(|number| Ok(vec![to_json_value(number)?]))
+            .collect::<Result<Vec<_>, Error>>()?;
+        self.batch_request("chain_getBlockHash", &params).await
+    }
+
     /// Get a block hash of the latest finalized block
     pub async fn finalized_head(&self) -> Result<T::Hash, Error> {
-        let hash = self.client.request("chain_getFinalizedHead", &[]).await?;
+        let hash = self.request("chain_getFinalizedHead", &[]).await?;
         Ok(hash)
     }
 
@@ -414,7 +771,7 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<Option<ChainBlock<T>>, Error> {
         let params = &[to_json_value(hash)?];
-        let block = self.client.request("chain_getBlock", params).await?;
+        let block = self.request("chain_getBlock", params).await?;
         Ok(block)
     }
 
@@ -425,7 +782,7 @@ impl<T: Runtime> Rpc<T> {
         hash: Option<T::Hash>,
     ) -> Result<ReadProof<T::Hash>, Error> {
         let params = &[to_json_value(keys)?, to_json_value(hash)?];
-        let proof = self.client.request("state_getReadProof", params).await?;
+        let proof = self.request("state_getReadProof", params).await?;
         Ok(proof)
     }
 
@@ -435,10 +792,7 @@ impl<T: Runtime> Rpc<T> {
         at: Option<T::Hash>,
     ) -> Result<RuntimeVersion, Error> {
         let params = &[to_json_value(at)?];
-        let version = self
-            .client
-            .request("state_getRuntimeVersion", params)
-            .await?;
+        let version = self.request("state_getRuntimeVersion", params).await?;
         Ok(version)
     }
 
@@ -447,14 +801,22 @@ impl<T: Runtime> Rpc<T> {
     /// *WARNING* these may not be included in the finalized chain, use
     /// `subscribe_finalized_events` to ensure events are finalized.
     pub async fn subscribe_events(&self) -> Result<EventStorageSubscription<T>, Error> {
-        let keys = Some(vec![StorageKey::from(SystemEvents::new())]);
-        let params = &[to_json_value(keys)?];
+        let keys = vec![StorageKey::from(SystemEvents::new())];
+        let subscription = self.subscribe_storage(keys).await?;
+        Ok(EventStorageSubscription::Imported(subscription))
+    }
 
+    /// Subscribe to changes of the values under the given storage keys.
+    pub async fn subscribe_storage(
+        &self,
+        keys: Vec<StorageKey>,
+    ) -> Result<Subscription<StorageChangeSet<T::Hash>>, Error> {
+        let params = &[to_json_value(Some(keys))?];
         let subscription = self
             .client
             .subscribe("state_subscribeStorage", params, "state_unsubscribeStorage")
             .await?;
-        Ok(EventStorageSubscription::Imported(subscription))
+        Ok(subscription)
     }
 
     /// Subscribe to finalized events.
@@ -501,10 +863,7 @@ impl<T: Runtime> Rpc<T> {
     ) -> Result<T::Hash, Error> {
         let bytes: Bytes = extrinsic.encode().into();
         let params = &[to_json_value(bytes)?];
-        let xt_hash = self
-            .client
-            .request("author_submitExtrinsic", params)
-            .await?;
+        let xt_hash = self.request("author_submitExtrinsic", params).await?;
         Ok(xt_hash)
     }
 
@@ -632,13 +991,13 @@ impl<T: Runtime> Rpc<T> {
             to_json_value(suri)?,
             to_json_value(public)?,
         ];
-        self.client.request("author_insertKey", params).await?;
+        self.request("author_insertKey", params).await?;
         Ok(())
     }
 
     /// Generate new session keys and returns the corresponding public keys.
     pub async fn rotate_keys(&self) -> Result<Bytes, Error> {
-        Ok(self.client.request("author_rotateKeys", &[]).await?)
+        self.request("author_rotateKeys", &[]).await
     }
 
     /// Checks if the keystore has private keys for the given session public keys.
@@ -648,7 +1007,7 @@ impl<T: Runtime> Rpc<T> {
     /// Returns `true` iff all private keys could be found.
     pub async fn has_session_keys(&self, session_keys: Bytes) -> Result<bool, Error> {
         let params = &[to_json_value(session_keys)?];
-        Ok(self.client.request("author_hasSessionKeys", params).await?)
+        self.request("author_hasSessionKeys", params).await
     }
 
     /// Checks if the keystore has private keys for the given public key and key type.
@@ -660,7 +1019,7 @@ impl<T: Runtime> Rpc<T> {
         key_type: String,
     ) -> Result<bool, Error> {
         let params = &[to_json_value(public_key)?, to_json_value(key_type)?];
-        Ok(self.client.request("author_hasKey", params).await?)
+        self.request("author_hasKey", params).await
     }
 }
 
@@ -695,3 +1054,140 @@ impl<T: System> ExtrinsicSuccess<T> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::{
+        io,
+        sync::Mutex,
+    };
+
+    type TestRuntime = crate::NodeTemplateRuntime;
+
+    /// Transport failing the first `failures` requests with `error`, and counting the
+    /// requests.
+    struct FlakyTransport {
+        failures: u32,
+        error: fn() -> Error,
+        requests: Arc<Mutex<u32>>,
+    }
+
+    #[async_trait::async_trait]
+    impl RpcTransport for FlakyTransport {
+        async fn request(
+            &self,
+            _method: &str,
+            _params: &[JsonValue],
+        ) -> Result<JsonValue, Error> {
+            let mut requests = self.requests.lock().unwrap();
+            *requests += 1;
+            if *requests <= self.failures {
+                return Err((self.error)())
+            }
+            Ok(JsonValue::Null)
+        }
+
+        async fn subscribe(
+            &self,
+            _subscribe_method: &str,
+            _params: &[JsonValue],
+            _unsubscribe_method: &str,
+        ) -> Result<Subscription<JsonValue>, Error> {
+            Err("Subscriptions not supported".into())
+        }
+    }
+
+    fn transport_error() -> Error {
+        let err = io::Error::new(io::ErrorKind::ConnectionReset, "connection reset");
+        RpcError::Transport(Box::new(err)).into()
+    }
+
+    /// Sends a request with 3 attempts to a transport failing `failures` times, returning
+    /// the result and the number of requests the transport received.
+    async fn request(
+        method: &str,
+        failures: u32,
+        error: fn() -> Error,
+    ) -> (Result<JsonValue, Error>, u32) {
+        let requests = Arc::new(Mutex::new(0));
+        let transport = FlakyTransport {
+            failures,
+            error,
+            requests: requests.clone(),
+        };
+        let mut rpc = Rpc::<TestRuntime>::new(RpcClient::new(transport));
+        rpc.set_retry_policy(RetryPolicy {
+            initial_backoff: Duration::from_millis(1),
+            ..RetryPolicy::exponential(3)
+        });
+        let result = rpc.request(method, &[]).await;
+        let requests = *requests.lock().unwrap();
+        (result, requests)
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            backoff_multiplier: 3,
+            max_backoff: Duration::from_secs(1),
+            ..RetryPolicy::exponential(10)
+        };
+        let backoffs: Vec<_> = (1..=4).map(|retry| policy.backoff(retry)).collect();
+        assert_eq!(
+            backoffs,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(300),
+                Duration::from_millis(900),
+                Duration::from_secs(1),
+            ]
+        );
+        // the multiplier saturates
+        assert_eq!(policy.backoff(100), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_is_transient() {
+        assert!(RetryPolicy::is_transient(&transport_error()));
+        assert!(RetryPolicy::is_transient(&RpcError::RequestTimeout.into()));
+        assert!(RetryPolicy::is_transient(
+            &RpcError::RestartNeeded("closed".into()).into()
+        ));
+        assert!(RetryPolicy::is_transient(&Error::Timeout(
+            "system_name".into()
+        )));
+        assert!(!RetryPolicy::is_transient(
+            &RpcError::Custom("error".into()).into()
+        ));
+        assert!(!RetryPolicy::is_transient(&"error".into()));
+    }
+
+    #[async_std::test]
+    async fn test_retry_transient_errors() {
+        let (result, requests) = request("system_name", 2, transport_error).await;
+        assert!(result.is_ok());
+        assert_eq!(requests, 3);
+
+        let (result, requests) = request("system_name", 3, transport_error).await;
+        assert!(RetryPolicy::is_transient(&result.unwrap_err()));
+        assert_eq!(requests, 3);
+    }
+
+    #[async_std::test]
+    async fn test_no_retry_of_permanent_errors() {
+        let (result, requests) = request("system_name", 1, || "error".into()).await;
+        assert!(result.is_err());
+        assert_eq!(requests, 1);
+    }
+
+    #[async_std::test]
+    async fn test_no_retry_of_non_idempotent_methods() {
+        for method in NON_IDEMPOTENT_METHODS {
+            let (result, requests) = request(method, 1, transport_error).await;
+            assert!(result.is_err());
+            assert_eq!(requests, 1);
+        }
+    }
+}
@@ -0,0 +1,458 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    sync::{
+        Arc,
+        RwLock,
+    },
+    time::{
+        Duration,
+        Instant,
+    },
+};
+
+use futures::{
+    future,
+    stream::{
+        self,
+        StreamExt,
+    },
+};
+use jsonrpsee_types::JsonValue;
+use serde::Deserialize;
+use sp_rpc::number::NumberOrHex;
+
+use super::{
+    Health,
+    RetryPolicy,
+    RpcClient,
+    RpcTransport,
+    Subscription,
+    NON_IDEMPOTENT_METHODS,
+};
+use crate::error::Error;
+
+/// Header fields needed to compare the best blocks of the endpoints.
+#[derive(Deserialize)]
+struct HeaderNumber {
+    number: NumberOrHex,
+}
+
+/// An endpoint of a [`FailoverClient`].
+struct Endpoint {
+    url: String,
+    /// Connection of the active endpoint, `None` for the others.
+    client: RwLock<Option<RpcClient>>,
+}
+
+impl Endpoint {
+    fn client(&self) -> Option<RpcClient> {
+        self.client.read().expect("lock poisoned; qed").clone()
+    }
+
+    /// Returns the best block number of the endpoint if it is healthy, and the client
+    /// used for the check.
+    ///
+    /// Reuses the connection of the active endpoint, and probes the others with a new
+    /// connection.
+    async fn check_health(&self) -> Result<(u64, RpcClient), Error> {
+        let client = match self.client() {
+            Some(client) => client,
+            None => RpcClient::connect(&self.url).await?,
+        };
+        let health: Health = client.request("system_health", &[]).await?;
+        if health.is_syncing {
+            return Err(format!("{} is syncing", self.url).into())
+        }
+        if health.should_have_peers && health.peers == 0 {
+            return Err(format!("{} has no peers", self.url).into())
+        }
+        let header: HeaderNumber = client.request("chain_getHeader", &[]).await?;
+        Ok((header.number.into_u256().low_u64(), client))
+    }
+}
+
+/// Builder for a [`FailoverClient`].
+pub struct FailoverClientBuilder {
+    urls: Vec<String>,
+    max_block_lag: u64,
+    health_check_interval: Duration,
+}
+
+impl FailoverClientBuilder {
+    /// Creates a new builder for a client connecting to the given urls, in order of
+    /// preference.
+    pub fn new<U: Into<String>>(urls: impl IntoIterator<Item = U>) -> Self {
+        Self {
+            urls: urls.into_iter().map(Into::into).collect(),
+            max_block_lag: 5,
+            health_check_interval: Duration::from_secs(60),
+        }
+    }
+
+    /// Set the number of blocks an endpoint may lag behind the best endpoint before it is
+    /// considered unhealthy.
+    pub fn set_max_block_lag(mut self, max_block_lag: u64) -> Self {
+        self.max_block_lag = max_block_lag;
+        self
+    }
+
+    /// Set the interval after which the endpoints are checked again, e.g. to move back to
+    /// a preferred endpoint which has recovered.
+    pub fn set_health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
+    /// Creates a new FailoverClient, connected to the first healthy endpoint.
+    pub async fn build(self) -> Result<FailoverClient, Error> {
+        if self.urls.is_empty() {
+            return Err("A failover client requires at least one url".into())
+        }
+        let endpoints = Endpoints {
+            endpoints: self
+                .urls
+                .into_iter()
+                .map(|url| {
+                    Endpoint {
+                        url,
+                        client: RwLock::new(None),
+                    }
+                })
+                .collect(),
+            active: RwLock::new(None),
+            last_check: RwLock::new(Instant::now()),
+            max_block_lag: self.max_block_lag,
+            health_check_interval: self.health_check_interval,
+        };
+        endpoints.select().await?;
+        Ok(FailoverClient {
+            endpoints: Arc::new(endpoints),
+        })
+    }
+}
+
+/// The endpoints of a [`FailoverClient`], shared with its subscriptions.
+struct Endpoints {
+    endpoints: Vec<Endpoint>,
+    active: RwLock<Option<usize>>,
+    last_check: RwLock<Instant>,
+    max_block_lag: u64,
+    health_check_interval: Duration,
+}
+
+impl Endpoints {
+    /// Checks the health of all endpoints, and makes the preferred healthy one active.
+    ///
+    /// Only the connection of the selected endpoint is kept, the probe connections to the
+    /// others are closed.
+    async fn select(&self) -> Result<(usize, RpcClient), Error> {
+        let results =
+            future::join_all(self.endpoints.iter().map(Endpoint::check_health)).await;
+        *self.last_check.write().expect("lock poisoned; qed") = Instant::now();
+        for (endpoint, result) in self.endpoints.iter().zip(&results) {
+            if let Err(err) = result {
+                log::warn!("Endpoint {} is unhealthy: {}", endpoint.url, err);
+            }
+        }
+        let best = results
+            .iter()
+            .filter_map(|result| result.as_ref().ok())
+            .map(|(number, _)| *number)
+            .max();
+        let selected = results.iter().position(|result| {
+            match (result, best) {
+                (Ok((number, _)), Some(best)) => {
+                    number.saturating_add(self.max_block_lag) >= best
+                }
+                _ => false,
+            }
+        });
+        for (index, (endpoint, result)) in self.endpoints.iter().zip(results).enumerate()
+        {
+            let client = match result {
+                Ok((_, client)) if Some(index) == selected => Some(client),
+                _ => None,
+            };
+            *endpoint.client.write().expect("lock poisoned; qed") = client;
+        }
+        *self.active.write().expect("lock poisoned; qed") = selected;
+        let index = selected.ok_or("No healthy endpoint")?;
+        let client = self.endpoints[index]
+            .client()
+            .ok_or("No healthy endpoint")?;
+        log::info!("Using endpoint {}", self.endpoints[index].url);
+        Ok((index, client))
+    }
+
+    /// Returns the active endpoint, selecting a new one if there is none or the endpoints
+    /// are due for a health check.
+    async fn active(&self) -> Result<(usize, RpcClient), Error> {
+        let active = *self.active.read().expect("lock poisoned; qed");
+        let due = self
+            .last_check
+            .read()
+            .expect("lock poisoned; qed")
+            .elapsed()
+            >= self.health_check_interval;
+        match active {
+            Some(index) if !due => {
+                if let Some(client) = self.endpoints[index].client() {
+                    return Ok((index, client))
+                }
+                self.select().await
+            }
+            _ => self.select().await,
+        }
+    }
+
+    /// Marks the endpoint as failed, so the next request selects a new one.
+    fn fail(&self, index: usize, err: &Error) {
+        log::warn!("Endpoint {} failed: {}", self.endpoints[index].url, err);
+        *self.endpoints[index]
+            .client
+            .write()
+            .expect("lock poisoned; qed") = None;
+        let mut active = self.active.write().expect("lock poisoned; qed");
+        if *active == Some(index) {
+            *active = None;
+        }
+    }
+
+    /// Subscribes on the active endpoint, failing over to the next healthy one if that
+    /// fails.
+    ///
+    /// Returns the client of the endpoint too, which keeps its connection open when
+    /// another endpoint becomes active.
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<(usize, RpcClient, Subscription<JsonValue>), Error> {
+        let mut attempts = 0;
+        loop {
+            let (index, client) = self.active().await?;
+            attempts += 1;
+            match client
+                .subscribe(subscribe_method, params, unsubscribe_method)
+                .await
+            {
+                Ok(subscription) => return Ok((index, client, subscription)),
+                Err(err)
+                    if RetryPolicy::is_transient(&err)
+                        && attempts < self.endpoints.len()
+                        && !NON_IDEMPOTENT_METHODS.contains(&subscribe_method) =>
+                {
+                    self.fail(index, &err);
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+/// Rpc client routing requests to the first healthy of several endpoints.
+///
+/// An endpoint is healthy if it is reachable, not syncing, has peers and its best block
+/// lags at most `max_block_lag` blocks behind the best block of all endpoints. When a
+/// request to the active endpoint fails with a transport error, the endpoints are checked
+/// and the request is sent to the next healthy one.
+pub struct FailoverClient {
+    endpoints: Arc<Endpoints>,
+}
+
+impl FailoverClient {
+    /// Returns the url of the active endpoint.
+    pub fn active_url(&self) -> Option<&str> {
+        let active = (*self.endpoints.active.read().expect("lock poisoned; qed"))?;
+        Some(&self.endpoints.endpoints[active].url)
+    }
+
+    /// Checks the health of all endpoints, and makes the preferred healthy one active.
+    pub async fn select_endpoint(&self) -> Result<(), Error> {
+        self.endpoints.select().await?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for FailoverClient {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        let mut attempts = 0;
+        loop {
+            let (index, client) = self.endpoints.active().await?;
+            attempts += 1;
+            match client.request(method, params).await {
+                Err(err)
+                    if RetryPolicy::is_transient(&err)
+                        && attempts < self.endpoints.endpoints.len()
+                        && !NON_IDEMPOTENT_METHODS.contains(&method) =>
+                {
+                    self.endpoints.fail(index, &err);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    async fn batch_request(
+        &self,
+        batch: &[(&str, &[JsonValue])],
+    ) -> Result<Vec<JsonValue>, Error> {
+        let mut attempts = 0;
+        loop {
+            let (index, client) = self.endpoints.active().await?;
+            attempts += 1;
+            match client.batch_request(batch).await {
+                Err(err)
+                    if RetryPolicy::is_transient(&err)
+                        && attempts < self.endpoints.endpoints.len()
+                        && !batch.iter().any(|(method, _)| {
+                            NON_IDEMPOTENT_METHODS.contains(method)
+                        }) =>
+                {
+                    self.endpoints.fail(index, &err);
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Start a JSON-RPC subscription on the active endpoint.
+    ///
+    /// When the subscription fails, or is closed by an endpoint which turns out to be
+    /// unhealthy, it is moved to the next healthy endpoint. Notifications the subscription
+    /// sends on start, e.g. the current value of a storage subscription, are repeated after
+    /// the move.
+    ///
+    /// Subscriptions with side effects, e.g. `author_submitAndWatchExtrinsic`, are not
+    /// moved, but end with the error instead.
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        let (index, client, subscription) = self
+            .endpoints
+            .subscribe(subscribe_method, params, unsubscribe_method)
+            .await?;
+        let moving = MovingSubscription {
+            endpoints: self.endpoints.clone(),
+            index,
+            _client: client,
+            subscription,
+            subscribe_method: subscribe_method.to_string(),
+            params: params.to_vec(),
+            unsubscribe_method: unsubscribe_method.to_string(),
+            ended: false,
+        };
+        Ok(Subscription::new(stream::unfold(moving, |mut moving| {
+            async move { moving.next().await.map(|notif| (notif, moving)) }
+        })))
+    }
+
+    /// Connects to all endpoints again, and makes the preferred healthy one active.
+    async fn reconnect(&self) -> Result<(), Error> {
+        for endpoint in &self.endpoints.endpoints {
+            *endpoint.client.write().expect("lock poisoned; qed") = None;
+        }
+        self.endpoints.select().await?;
+        Ok(())
+    }
+
+    fn can_reconnect(&self) -> bool {
+        true
+    }
+}
+
+/// Subscription which moves to the next healthy endpoint when its endpoint fails.
+struct MovingSubscription {
+    endpoints: Arc<Endpoints>,
+    index: usize,
+    /// Client of the endpoint, keeping the connection open if it is no longer active.
+    _client: RpcClient,
+    subscription: Subscription<JsonValue>,
+    subscribe_method: String,
+    params: Vec<JsonValue>,
+    unsubscribe_method: String,
+    ended: bool,
+}
+
+impl MovingSubscription {
+    /// Returns the next notification, or `None` once the subscription ended.
+    ///
+    /// A subscription closed by a healthy endpoint ends. A subscription which can't be
+    /// moved ends after returning the error.
+    async fn next(&mut self) -> Option<Result<JsonValue, Error>> {
+        if self.ended {
+            return None
+        }
+        loop {
+            let err = match self.subscription.notifs.next().await {
+                Some(Ok(notif)) => return Some(Ok(notif)),
+                Some(Err(err)) => err,
+                None => {
+                    let endpoint = &self.endpoints.endpoints[self.index];
+                    match endpoint.check_health().await {
+                        Ok(_) => return None,
+                        Err(err) => err,
+                    }
+                }
+            };
+            self.endpoints.fail(self.index, &err);
+            if NON_IDEMPOTENT_METHODS.contains(&self.subscribe_method.as_str()) {
+                self.ended = true;
+                return Some(Err(err))
+            }
+            match self
+                .endpoints
+                .subscribe(
+                    &self.subscribe_method,
+                    &self.params,
+                    &self.unsubscribe_method,
+                )
+                .await
+            {
+                Ok((index, client, subscription)) => {
+                    log::info!(
+                        "Moved subscription {} to {}",
+                        self.subscribe_method,
+                        self.endpoints.endpoints[index].url
+                    );
+                    self.index = index;
+                    self._client = client;
+                    self.subscription = subscription;
+                }
+                Err(err) => {
+                    log::error!(
+                        "Moving subscription {} failed: {}",
+                        self.subscribe_method,
+                        err
+                    );
+                    self.ended = true;
+                    return Some(Err(err))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,401 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    collections::HashMap,
+    io::{
+        self,
+        BufReader,
+        Write,
+    },
+    net::Shutdown,
+    os::unix::net::UnixStream,
+    path::Path,
+    sync::{
+        atomic::{
+            AtomicU64,
+            Ordering,
+        },
+        Arc,
+        Mutex,
+    },
+    thread,
+};
+
+use futures::{
+    channel::{
+        mpsc,
+        oneshot,
+    },
+    executor,
+    stream::{
+        self,
+        StreamExt,
+    },
+};
+use jsonrpsee_types::{
+    v2::error::JsonRpcErrorAlloc,
+    Error as RpcError,
+    JsonValue,
+};
+
+use super::{
+    RpcTransport,
+    Subscription,
+};
+use crate::error::Error;
+
+type SubscribeResult = Result<(JsonValue, mpsc::UnboundedReceiver<JsonValue>), Error>;
+
+/// A request waiting for its response.
+enum Pending {
+    Request(oneshot::Sender<Result<JsonValue, Error>>),
+    Subscribe(oneshot::Sender<SubscribeResult>),
+}
+
+/// Connection shared by the client, its reader thread and its subscriptions.
+struct Connection {
+    stream: UnixStream,
+    /// Messages to the node, written by the writer thread so sending never blocks.
+    messages: mpsc::UnboundedSender<Vec<u8>>,
+    next_id: AtomicU64,
+    /// `None` once the connection is closed.
+    pending: Mutex<Option<HashMap<u64, Pending>>>,
+    /// Notification senders by subscription id, encoded as JSON.
+    subscriptions: Mutex<HashMap<String, mpsc::UnboundedSender<JsonValue>>>,
+}
+
+impl Connection {
+    /// Sends a request, registering `pending` to receive its response.
+    fn send(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+        pending: Option<Pending>,
+    ) -> Result<(), Error> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "method": method,
+            "params": params,
+        });
+        let mut message = serde_json::to_vec(&request)?;
+        message.push(b'\n');
+        if let Some(pending) = pending {
+            self.pending
+                .lock()
+                .expect("lock poisoned; qed")
+                .as_mut()
+                .ok_or_else(closed)?
+                .insert(id, pending);
+        }
+        if self.messages.unbounded_send(message).is_err() {
+            if let Some(pending) =
+                self.pending.lock().expect("lock poisoned; qed").as_mut()
+            {
+                pending.remove(&id);
+            }
+            return Err(closed())
+        }
+        Ok(())
+    }
+
+    /// Dispatches a response or notification from the node.
+    fn handle(&self, mut message: JsonValue) {
+        if let Some(id) = message.get("id").and_then(JsonValue::as_u64) {
+            let pending = self
+                .pending
+                .lock()
+                .expect("lock poisoned; qed")
+                .as_mut()
+                .and_then(|pending| pending.remove(&id));
+            let result = if message.get("error").is_some() {
+                Err(response_error(message))
+            } else {
+                Ok(message["result"].take())
+            };
+            match pending {
+                Some(Pending::Request(sender)) => {
+                    let _ = sender.send(result);
+                }
+                Some(Pending::Subscribe(sender)) => {
+                    // Registered here, because notifications may follow the response
+                    // before the subscriber receives it.
+                    let result = result.map(|id| {
+                        let (notifs_tx, notifs_rx) = mpsc::unbounded();
+                        self.subscriptions
+                            .lock()
+                            .expect("lock poisoned; qed")
+                            .insert(id.to_string(), notifs_tx);
+                        (id, notifs_rx)
+                    });
+                    let _ = sender.send(result);
+                }
+                // E.g. the response to an unsubscribe request.
+                None => {}
+            }
+        } else if let Some(params) = message.get_mut("params") {
+            let id = params.get("subscription").map(ToString::to_string);
+            let result = params.get_mut("result").map(JsonValue::take);
+            if let (Some(id), Some(result)) = (id, result) {
+                let subscriptions =
+                    self.subscriptions.lock().expect("lock poisoned; qed");
+                if let Some(notifs) = subscriptions.get(&id) {
+                    let _ = notifs.unbounded_send(result);
+                }
+            }
+        }
+    }
+
+    /// Fails all pending requests and closes all subscriptions.
+    fn close(&self) {
+        let pending = self.pending.lock().expect("lock poisoned; qed").take();
+        for (_, pending) in pending.into_iter().flatten() {
+            match pending {
+                Pending::Request(sender) => {
+                    let _ = sender.send(Err(closed()));
+                }
+                Pending::Subscribe(sender) => {
+                    let _ = sender.send(Err(closed()));
+                }
+            }
+        }
+        self.subscriptions
+            .lock()
+            .expect("lock poisoned; qed")
+            .clear();
+    }
+}
+
+/// Returns the error for requests on a closed connection.
+fn closed() -> Error {
+    let err = io::Error::new(io::ErrorKind::ConnectionAborted, "IPC connection closed");
+    RpcError::Transport(Box::new(err)).into()
+}
+
+/// Returns the error of a failed response, like the other jsonrpsee transports.
+fn response_error(response: JsonValue) -> Error {
+    match serde_json::from_value::<JsonRpcErrorAlloc>(response) {
+        Ok(err) => RpcError::Request(err).into(),
+        Err(err) => RpcError::ParseError(err).into(),
+    }
+}
+
+/// Writes the messages to the node until the connection is closed.
+fn write_messages(mut writer: UnixStream, messages: mpsc::UnboundedReceiver<Vec<u8>>) {
+    for message in executor::block_on_stream(messages) {
+        if let Err(err) = writer.write_all(&message) {
+            log::warn!("IPC connection failed: {}", err);
+            // Stops the reader thread, which fails the pending requests.
+            let _ = writer.shutdown(Shutdown::Both);
+            break
+        }
+    }
+}
+
+/// Reads the messages from the node until the connection is closed.
+fn read_messages(connection: Arc<Connection>, reader: UnixStream) {
+    let messages =
+        serde_json::Deserializer::from_reader(BufReader::new(reader)).into_iter();
+    for message in messages {
+        match message {
+            Ok(message) => connection.handle(message),
+            Err(err) => {
+                if !err.is_eof() {
+                    log::warn!("IPC connection failed: {}", err);
+                }
+                break
+            }
+        }
+    }
+    connection.close();
+}
+
+/// Unsubscribes when the subscription is dropped.
+struct Unsubscribe {
+    connection: Arc<Connection>,
+    method: String,
+    id: JsonValue,
+}
+
+impl Drop for Unsubscribe {
+    fn drop(&mut self) {
+        self.connection
+            .subscriptions
+            .lock()
+            .expect("lock poisoned; qed")
+            .remove(&self.id.to_string());
+        if let Err(err) = self.connection.send(&self.method, &[self.id.clone()], None) {
+            log::debug!("Unsubscribing with {} failed: {}", self.method, err);
+        }
+    }
+}
+
+/// Rpc client connected to the IPC socket of a node.
+///
+/// Requests are written and responses read on dedicated threads, so the client doesn't
+/// depend on an async runtime. Batch requests are sent as separate requests.
+pub struct IpcClient {
+    connection: Arc<Connection>,
+}
+
+impl IpcClient {
+    /// Connects to the Unix domain socket at `path`.
+    pub fn connect<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let stream = UnixStream::connect(path)?;
+        let reader = stream.try_clone()?;
+        let writer = stream.try_clone()?;
+        let (messages_tx, messages_rx) = mpsc::unbounded();
+        let connection = Arc::new(Connection {
+            stream,
+            messages: messages_tx,
+            next_id: AtomicU64::new(0),
+            pending: Mutex::new(Some(HashMap::new())),
+            subscriptions: Mutex::new(HashMap::new()),
+        });
+        thread::Builder::new()
+            .name("subxt-ipc-writer".into())
+            .spawn(move || write_messages(writer, messages_rx))?;
+        let reader_connection = connection.clone();
+        thread::Builder::new()
+            .name("subxt-ipc".into())
+            .spawn(move || read_messages(reader_connection, reader))?;
+        Ok(Self { connection })
+    }
+}
+
+impl Drop for IpcClient {
+    fn drop(&mut self) {
+        // Stops the reader thread, which closes the subscriptions.
+        let _ = self.connection.stream.shutdown(Shutdown::Both);
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for IpcClient {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        let (sender, receiver) = oneshot::channel();
+        self.connection
+            .send(method, params, Some(Pending::Request(sender)))?;
+        receiver.await.map_err(|_| closed())?
+    }
+
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        let (sender, receiver) = oneshot::channel();
+        self.connection.send(
+            subscribe_method,
+            params,
+            Some(Pending::Subscribe(sender)),
+        )?;
+        let (id, notifs) = receiver.await.map_err(|_| closed())??;
+        let unsubscribe = Unsubscribe {
+            connection: self.connection.clone(),
+            method: unsubscribe_method.to_string(),
+            id,
+        };
+        Ok(Subscription::new(stream::unfold(
+            (notifs, unsubscribe),
+            |(mut notifs, unsubscribe)| {
+                async move {
+                    notifs
+                        .next()
+                        .await
+                        .map(|notif| (Ok(notif), (notifs, unsubscribe)))
+                }
+            },
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RpcClient;
+    use std::{
+        io::BufRead,
+        os::unix::net::UnixListener,
+    };
+
+    /// Answers requests with their method name, `fail` with an error, and subscriptions
+    /// with two notifications.
+    fn serve(listener: UnixListener) {
+        let (stream, _) = listener.accept().unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        for line in BufReader::new(stream).lines() {
+            let request: JsonValue = serde_json::from_str(&line.unwrap()).unwrap();
+            let method = request["method"].as_str().unwrap();
+            let result = if method == "subscribe" { "sub" } else { method };
+            let response = if method == "fail" {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "error": { "code": -32601, "message": "Method not found" },
+                })
+            } else {
+                serde_json::json!({
+                    "jsonrpc": "2.0",
+                    "id": request["id"],
+                    "result": result,
+                })
+            };
+            writeln!(writer, "{}", response).unwrap();
+            if method == "subscribe" {
+                for result in &[1, 2] {
+                    let notif = serde_json::json!({
+                        "jsonrpc": "2.0",
+                        "method": "notify",
+                        "params": { "subscription": "sub", "result": result },
+                    });
+                    writeln!(writer, "{}", notif).unwrap();
+                }
+            }
+        }
+    }
+
+    #[async_std::test]
+    async fn test_ipc_client() {
+        let dir = tempdir::TempDir::new("subxt-ipc").unwrap();
+        let path = dir.path().join("node.ipc");
+        let listener = UnixListener::bind(&path).unwrap();
+        thread::spawn(move || serve(listener));
+
+        let url = format!("ipc://{}", path.display());
+        let client = RpcClient::connect(&url).await.unwrap();
+        let method: String = client.request("system_name", &[]).await.unwrap();
+        assert_eq!(method, "system_name");
+
+        let err = client.request::<String>("fail", &[]).await.unwrap_err();
+        assert!(matches!(err, Error::Rpc(RpcError::Request(_))));
+        assert!(err.to_string().contains("Method not found"));
+
+        let mut sub = client
+            .subscribe::<u32>("subscribe", &[], "unsubscribe")
+            .await
+            .unwrap();
+        assert_eq!(sub.next().await.unwrap(), Some(1));
+        assert_eq!(sub.next().await.unwrap(), Some(2));
+    }
+}
@@ -0,0 +1,358 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::{
+    fs::File,
+    io::{
+        BufReader,
+        BufWriter,
+    },
+    path::Path,
+    sync::{
+        Arc,
+        Mutex,
+    },
+};
+
+use futures::stream::{
+    self,
+    StreamExt,
+};
+use jsonrpsee_types::JsonValue;
+use serde::{
+    Deserialize,
+    Serialize,
+};
+
+use super::{
+    RpcClient,
+    RpcTransport,
+    Subscription,
+};
+use crate::error::Error;
+
+/// A recorded JSON-RPC call.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TraceEntry {
+    /// A request and its response.
+    Request {
+        /// The rpc method.
+        method: String,
+        /// The request parameters.
+        params: Vec<JsonValue>,
+        /// The result, if the request succeeded.
+        result: Option<JsonValue>,
+        /// The error message, if the request failed.
+        error: Option<String>,
+    },
+    /// A subscription and the notifications received.
+    Subscription {
+        /// The subscribe method.
+        method: String,
+        /// The subscription parameters.
+        params: Vec<JsonValue>,
+        /// The notifications, in the order they were received.
+        notifications: Vec<JsonValue>,
+    },
+}
+
+impl TraceEntry {
+    fn matches(&self, subscription: bool, method: &str, params: &[JsonValue]) -> bool {
+        match self {
+            Self::Request {
+                method: m,
+                params: p,
+                ..
+            } => !subscription && m == method && p == params,
+            Self::Subscription {
+                method: m,
+                params: p,
+                ..
+            } => subscription && m == method && p == params,
+        }
+    }
+}
+
+/// Transport recording the calls to another transport, e.g. to replay them with a
+/// [`ReplayTransport`] in tests.
+///
+/// Clones share the recorded trace, so keep a clone to save it after passing one to
+/// `ClientBuilder::set_client`.
+#[derive(Clone)]
+pub struct RecordingTransport {
+    inner: RpcClient,
+    trace: Arc<Mutex<Vec<TraceEntry>>>,
+}
+
+impl RecordingTransport {
+    /// Creates a new transport recording the calls to `inner`.
+    pub fn new<C: Into<RpcClient>>(inner: C) -> Self {
+        Self {
+            inner: inner.into(),
+            trace: Default::default(),
+        }
+    }
+
+    /// Returns the calls recorded so far.
+    pub fn trace(&self) -> Vec<TraceEntry> {
+        self.trace.lock().expect("lock poisoned; qed").clone()
+    }
+
+    /// Saves the calls recorded so far as JSON to the file at `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let writer = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(writer, &self.trace())?;
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for RecordingTransport {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        let result = self.inner.request(method, params).await;
+        let (recorded_result, error) = match &result {
+            Ok(result) => (Some(result.clone()), None),
+            Err(err) => (None, Some(err.to_string())),
+        };
+        self.trace
+            .lock()
+            .expect("lock poisoned; qed")
+            .push(TraceEntry::Request {
+                method: method.to_string(),
+                params: params.to_vec(),
+                result: recorded_result,
+                error,
+            });
+        result
+    }
+
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        let subscription: Subscription<JsonValue> = self
+            .inner
+            .subscribe(subscribe_method, params, unsubscribe_method)
+            .await?;
+        let index = {
+            let mut trace = self.trace.lock().expect("lock poisoned; qed");
+            trace.push(TraceEntry::Subscription {
+                method: subscribe_method.to_string(),
+                params: params.to_vec(),
+                notifications: Vec::new(),
+            });
+            trace.len() - 1
+        };
+        let trace = self.trace.clone();
+        Ok(Subscription::new(subscription.notifs.inspect(
+            move |notif| {
+                if let Ok(notif) = notif {
+                    let mut trace = trace.lock().expect("lock poisoned; qed");
+                    if let TraceEntry::Subscription { notifications, .. } =
+                        &mut trace[index]
+                    {
+                        notifications.push(notif.clone());
+                    }
+                }
+            },
+        )))
+    }
+
+    async fn reconnect(&self) -> Result<(), Error> {
+        self.inner.reconnect().await
+    }
+
+    fn can_reconnect(&self) -> bool {
+        self.inner.can_reconnect()
+    }
+}
+
+/// Transport replaying the calls recorded by a [`RecordingTransport`].
+///
+/// Calls are answered by the first unused recorded call with the same method and
+/// parameters, or the last one if all were used, so repeated calls replay in the recorded
+/// order. Replayed subscriptions close after the recorded notifications.
+///
+/// Extrinsics signed with a randomized signature scheme, e.g. sr25519, differ from the
+/// recorded ones, so watching them can only be replayed with deterministic signatures.
+pub struct ReplayTransport {
+    trace: Vec<TraceEntry>,
+    used: Mutex<Vec<bool>>,
+}
+
+impl ReplayTransport {
+    /// Creates a new transport replaying the given calls.
+    pub fn new(trace: Vec<TraceEntry>) -> Self {
+        let used = Mutex::new(vec![false; trace.len()]);
+        Self { trace, used }
+    }
+
+    /// Loads the calls to replay from a file saved with [`RecordingTransport::save`].
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let reader = BufReader::new(File::open(path)?);
+        Ok(Self::new(serde_json::from_reader(reader)?))
+    }
+
+    fn replay(
+        &self,
+        subscription: bool,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<&TraceEntry, Error> {
+        let mut used = self.used.lock().expect("lock poisoned; qed");
+        let matching = self
+            .trace
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.matches(subscription, method, params));
+        let unused = matching.clone().find(|(index, _)| !used[*index]);
+        match unused.or_else(|| matching.last()) {
+            Some((index, entry)) => {
+                used[index] = true;
+                Ok(entry)
+            }
+            None => {
+                Err(
+                    format!("No recorded call to {} with params {:?}", method, params)
+                        .into(),
+                )
+            }
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for ReplayTransport {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        match self.replay(false, method, params)? {
+            TraceEntry::Request {
+                error: Some(error), ..
+            } => Err(Error::Other(error.clone())),
+            TraceEntry::Request { result, .. } => {
+                Ok(result.clone().unwrap_or(JsonValue::Null))
+            }
+            TraceEntry::Subscription { .. } => {
+                unreachable!("requests match requests; qed")
+            }
+        }
+    }
+
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        _unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        match self.replay(true, subscribe_method, params)? {
+            TraceEntry::Subscription { notifications, .. } => {
+                Ok(Subscription::new(stream::iter(
+                    notifications.clone().into_iter().map(Ok),
+                )))
+            }
+            TraceEntry::Request { .. } => {
+                unreachable!("subscriptions match subscriptions; qed")
+            }
+        }
+    }
+
+    /// There is no connection, so reconnecting always succeeds.
+    async fn reconnect(&self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn can_reconnect(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Transport answering every request with a counter, and subscriptions with the
+    /// params.
+    #[derive(Default)]
+    struct CountingTransport(Mutex<u32>);
+
+    #[async_trait::async_trait]
+    impl RpcTransport for CountingTransport {
+        async fn request(
+            &self,
+            method: &str,
+            _params: &[JsonValue],
+        ) -> Result<JsonValue, Error> {
+            if method == "fail" {
+                return Err("failed".into())
+            }
+            let mut counter = self.0.lock().unwrap();
+            *counter += 1;
+            Ok((*counter).into())
+        }
+
+        async fn subscribe(
+            &self,
+            _subscribe_method: &str,
+            params: &[JsonValue],
+            _unsubscribe_method: &str,
+        ) -> Result<Subscription<JsonValue>, Error> {
+            Ok(Subscription::new(stream::iter(
+                params.to_vec().into_iter().map(Ok),
+            )))
+        }
+    }
+
+    #[async_std::test]
+    async fn test_record_and_replay() {
+        let recorder = RecordingTransport::new(CountingTransport::default());
+        let client = RpcClient::new(recorder.clone());
+        assert_eq!(client.request::<u32>("count", &[]).await.unwrap(), 1);
+        assert_eq!(client.request::<u32>("count", &[]).await.unwrap(), 2);
+        assert!(client.request::<u32>("fail", &[]).await.is_err());
+        let mut sub = client
+            .subscribe::<u32>("subscribe", &[3.into(), 4.into()], "unsubscribe")
+            .await
+            .unwrap();
+        assert_eq!(sub.next().await.unwrap(), Some(3));
+
+        let dir = tempdir::TempDir::new("subxt-trace").unwrap();
+        let path = dir.path().join("trace.json");
+        recorder.save(&path).unwrap();
+
+        let client = RpcClient::new(ReplayTransport::load(&path).unwrap());
+        assert_eq!(client.request::<u32>("count", &[]).await.unwrap(), 1);
+        assert_eq!(client.request::<u32>("count", &[]).await.unwrap(), 2);
+        assert_eq!(client.request::<u32>("count", &[]).await.unwrap(), 2);
+        assert!(client.request::<u32>("fail", &[]).await.is_err());
+        assert!(client.request::<u32>("missing", &[]).await.is_err());
+        let mut sub = client
+            .subscribe::<u32>("subscribe", &[3.into(), 4.into()], "unsubscribe")
+            .await
+            .unwrap();
+        assert_eq!(sub.next().await.unwrap(), Some(3));
+        assert_eq!(sub.next().await.unwrap(), None);
+    }
+}
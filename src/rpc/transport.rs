@@ -0,0 +1,334 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+use core::{
+    marker::PhantomData,
+    pin::Pin,
+    task::{
+        Context,
+        Poll,
+    },
+};
+
+use futures::{
+    future,
+    ready,
+    stream::{
+        self,
+        BoxStream,
+        Stream,
+        StreamExt,
+    },
+};
+use jsonrpsee_http_client::HttpClient;
+use jsonrpsee_types::{
+    traits::{
+        Client,
+        SubscriptionClient,
+    },
+    DeserializeOwned,
+    Error as RpcError,
+    JsonValue,
+};
+use jsonrpsee_ws_client::WsClient;
+#[cfg(feature = "client")]
+use substrate_subxt_client::SubxtClient;
+
+use crate::error::Error;
+
+/// Transport for JSON-RPC requests and subscriptions.
+///
+/// Implemented for the jsonrpsee WebSocket and HTTP clients, the embedded node and the
+/// [`FailoverClient`](super::FailoverClient). Implement it for a custom transport and pass
+/// it to `ClientBuilder::set_client`.
+#[async_trait::async_trait]
+pub trait RpcTransport: Send + Sync {
+    /// Start a JSON-RPC request.
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error>;
+
+    /// Start a JSON-RPC batch request, returning the results in the order of the requests.
+    ///
+    /// By default the requests are sent concurrently.
+    async fn batch_request(
+        &self,
+        batch: &[(&str, &[JsonValue])],
+    ) -> Result<Vec<JsonValue>, Error> {
+        future::try_join_all(
+            batch
+                .iter()
+                .map(|(method, params)| self.request(method, params)),
+        )
+        .await
+    }
+
+    /// Start a JSON-RPC subscription.
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error>;
+
+    /// Reconnects after the connection was lost, so later requests and subscriptions use
+    /// the new connection.
+    ///
+    /// Not supported by default, see [`RpcTransport::can_reconnect`].
+    async fn reconnect(&self) -> Result<(), Error> {
+        Err("Reconnecting not supported by this transport".into())
+    }
+
+    /// Returns `true` if the transport supports [`RpcTransport::reconnect`].
+    fn can_reconnect(&self) -> bool {
+        false
+    }
+}
+
+/// Active JSON-RPC subscription, unsubscribing when dropped.
+pub struct Subscription<T> {
+    pub(super) notifs: BoxStream<'static, Result<JsonValue, Error>>,
+    marker: PhantomData<T>,
+}
+
+impl<T> Subscription<T> {
+    /// Creates a subscription from a stream of notifications.
+    ///
+    /// The subscription is closed when the stream ends, and unsubscribing is left to
+    /// dropping the stream.
+    pub fn new<S>(notifs: S) -> Self
+    where
+        S: Stream<Item = Result<JsonValue, Error>> + Send + 'static,
+    {
+        Self {
+            notifs: notifs.boxed(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Changes the type the notifications are decoded to.
+    pub(super) fn cast<U>(self) -> Subscription<U> {
+        Subscription {
+            notifs: self.notifs,
+            marker: PhantomData,
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Subscription<T> {
+    /// Returns the next notification, or `None` if the subscription was closed.
+    pub async fn next(&mut self) -> Result<Option<T>, Error> {
+        match self.notifs.next().await {
+            Some(notif) => Ok(Some(serde_json::from_value(notif?)?)),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Stream for Subscription<T> {
+    type Item = Result<T, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let notif = ready!(self.notifs.poll_next_unpin(cx));
+        Poll::Ready(notif.map(|notif| Ok(serde_json::from_value(notif?)?)))
+    }
+}
+
+// The notifications are boxed, so the subscription doesn't need to be pinned.
+impl<T> Unpin for Subscription<T> {}
+
+impl From<jsonrpsee_types::Subscription<JsonValue>> for Subscription<JsonValue> {
+    fn from(subscription: jsonrpsee_types::Subscription<JsonValue>) -> Self {
+        Subscription::new(stream::unfold(subscription, |mut subscription| {
+            async move {
+                match subscription.next().await {
+                    Ok(Some(notif)) => Some((Ok(notif), subscription)),
+                    Ok(None) => None,
+                    Err(err) => Some((Err(err.into()), subscription)),
+                }
+            }
+        }))
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for WsClient {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        Ok(Client::request(self, method, params.into()).await?)
+    }
+
+    async fn batch_request(
+        &self,
+        batch: &[(&str, &[JsonValue])],
+    ) -> Result<Vec<JsonValue>, Error> {
+        let batch = batch
+            .iter()
+            .map(|(method, params)| (*method, (*params).into()))
+            .collect();
+        Ok(Client::batch_request(self, batch).await?)
+    }
+
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        let subscription = SubscriptionClient::subscribe(
+            self,
+            subscribe_method,
+            params.into(),
+            unsubscribe_method,
+        )
+        .await?;
+        Ok(subscription.into())
+    }
+}
+
+#[async_trait::async_trait]
+impl RpcTransport for HttpClient {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        Ok(Client::request(self, method, params.into()).await?)
+    }
+
+    async fn batch_request(
+        &self,
+        batch: &[(&str, &[JsonValue])],
+    ) -> Result<Vec<JsonValue>, Error> {
+        let batch = batch
+            .iter()
+            .map(|(method, params)| (*method, (*params).into()))
+            .collect();
+        Ok(Client::batch_request(self, batch).await?)
+    }
+
+    async fn subscribe(
+        &self,
+        _subscribe_method: &str,
+        _params: &[JsonValue],
+        _unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        Err(
+            RpcError::Custom("Subscriptions not supported on HTTP transport".to_owned())
+                .into(),
+        )
+    }
+}
+
+/// The embedded node doesn't support batches, so batch requests are sent concurrently.
+#[cfg(feature = "client")]
+#[async_trait::async_trait]
+impl RpcTransport for SubxtClient {
+    async fn request(
+        &self,
+        method: &str,
+        params: &[JsonValue],
+    ) -> Result<JsonValue, Error> {
+        Ok(SubxtClient::request(self, method, params.into()).await?)
+    }
+
+    async fn subscribe(
+        &self,
+        subscribe_method: &str,
+        params: &[JsonValue],
+        unsubscribe_method: &str,
+    ) -> Result<Subscription<JsonValue>, Error> {
+        let subscription = SubxtClient::subscribe(
+            self,
+            subscribe_method,
+            params.into(),
+            unsubscribe_method,
+        )
+        .await?;
+        Ok(subscription.into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::RpcClient;
+
+    /// Transport answering every request with its method name.
+    struct EchoTransport;
+
+    #[async_trait::async_trait]
+    impl RpcTransport for EchoTransport {
+        async fn request(
+            &self,
+            method: &str,
+            _params: &[JsonValue],
+        ) -> Result<JsonValue, Error> {
+            Ok(method.into())
+        }
+
+        async fn subscribe(
+            &self,
+            subscribe_method: &str,
+            params: &[JsonValue],
+            _unsubscribe_method: &str,
+        ) -> Result<Subscription<JsonValue>, Error> {
+            let notifs = vec![Ok(subscribe_method.into())]
+                .into_iter()
+                .chain(params.to_vec().into_iter().map(Ok));
+            Ok(Subscription::new(stream::iter(notifs)))
+        }
+    }
+
+    #[async_std::test]
+    async fn test_custom_transport() {
+        let client = RpcClient::new(EchoTransport);
+        let method: String = client.request("system_name", &[]).await.unwrap();
+        assert_eq!(method, "system_name");
+
+        let batch: &[(&str, &[JsonValue])] = &[("a", &[]), ("b", &[])];
+        let methods: Vec<String> = client.batch_request(batch).await.unwrap();
+        assert_eq!(methods, vec!["a", "b"]);
+
+        let mut sub = client
+            .subscribe::<String>("subscribe", &["x".into()], "unsubscribe")
+            .await
+            .unwrap();
+        assert_eq!(sub.next().await.unwrap(), Some("subscribe".into()));
+        assert_eq!(sub.next().await.unwrap(), Some("x".into()));
+        assert_eq!(sub.next().await.unwrap(), None);
+    }
+
+    #[async_std::test]
+    async fn test_subscription_stream() {
+        let client = RpcClient::new(EchoTransport);
+        let sub = client
+            .subscribe::<String>("subscribe", &["x".into(), 1.into()], "unsubscribe")
+            .await
+            .unwrap();
+        let notifs: Vec<_> = sub.collect().await;
+        assert_eq!(notifs.len(), 3);
+        assert_eq!(notifs[0].as_ref().unwrap(), "subscribe");
+        assert_eq!(notifs[1].as_ref().unwrap(), "x");
+        // a notification of the wrong type is an error, not the end of the stream
+        assert!(notifs[2].is_err());
+    }
+}
@@ -14,19 +14,49 @@
 // You should have received a copy of the GNU General Public License
 // along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
 
-use jsonrpsee_types::{
-    DeserializeOwned,
-    Subscription,
+use codec::{
+    Decode,
+    Encode,
 };
+use futures::{
+    future::{
+        self,
+        BoxFuture,
+    },
+    ready,
+    stream::{
+        Stream,
+        StreamExt,
+    },
+    FutureExt,
+};
+use futures_timer::Delay;
+use jsonrpsee_types::DeserializeOwned;
 use sp_core::{
     storage::{
         StorageChangeSet,
+        StorageData,
         StorageKey,
     },
     twox_128,
 };
-use sp_runtime::traits::Header;
-use std::collections::VecDeque;
+use sp_runtime::traits::{
+    Header,
+    Saturating,
+};
+use std::{
+    collections::{
+        HashMap,
+        VecDeque,
+    },
+    marker::PhantomData,
+    pin::Pin,
+    task::{
+        Context,
+        Poll,
+    },
+    time::Duration,
+};
 
 use crate::{
     error::Error,
@@ -38,11 +68,17 @@ use crate::{
     frame::{
         system::Phase,
         Event,
+        RuntimeEvent,
+    },
+    rpc::{
+        Rpc,
+        Subscription,
     },
-    rpc::Rpc,
     runtimes::Runtime,
 };
 
+type EventPredicate<'a> = Box<dyn Fn(&RawEvent) -> bool + Send + Sync + 'a>;
+
 /// Event subscription simplifies filtering a storage change set stream for
 /// events of interest.
 pub struct EventSubscription<'a, T: Runtime> {
@@ -50,8 +86,13 @@ pub struct EventSubscription<'a, T: Runtime> {
     decoder: &'a EventsDecoder<T>,
     block: Option<T::Hash>,
     extrinsic: Option<usize>,
-    event: Option<(&'static str, &'static str)>,
+    phase: Option<Phase>,
+    event_filter: Vec<(&'static str, &'static str)>,
+    module_filter: Vec<&'static str>,
+    predicates: Vec<EventPredicate<'a>>,
+    topic: Option<Vec<u8>>,
     events: VecDeque<RawEvent>,
+    skip_undecodable: bool,
     finished: bool,
 }
 
@@ -66,8 +107,13 @@ impl<'a, T: Runtime> EventSubscription<'a, T> {
             decoder,
             block: None,
             extrinsic: None,
-            event: None,
+            phase: None,
+            event_filter: Vec::new(),
+            module_filter: Vec::new(),
+            predicates: Vec::new(),
+            topic: None,
             events: Default::default(),
+            skip_undecodable: false,
             finished: false,
         }
     }
@@ -83,60 +129,161 @@ impl<'a, T: Runtime> EventSubscription<'a, T> {
         self.extrinsic = Some(ext_index);
     }
 
+    /// Only returns events emitted in the given phase, e.g. `Phase::Initialization` for
+    /// events emitted by `on_initialize` hooks.
+    pub fn filter_phase(&mut self, phase: Phase) {
+        self.phase = Some(phase);
+    }
+
     /// Filters events by type.
+    ///
+    /// Can be called multiple times, returning events of any of the given types or of the
+    /// modules passed to `filter_module`.
     pub fn filter_event<E: Event<T>>(&mut self) {
-        self.event = Some((E::MODULE, E::EVENT));
+        self.event_filter.push((E::MODULE, E::EVENT));
+    }
+
+    /// Filters events by module.
+    ///
+    /// Can be called multiple times, returning events of any of the given modules or of the
+    /// types passed to `filter_event`.
+    pub fn filter_module(&mut self, module: &'static str) {
+        self.module_filter.push(module);
+    }
+
+    /// Only returns events for which `predicate` returns `true`.
+    ///
+    /// Can be called multiple times, returning events that satisfy all predicates.
+    pub fn filter<F>(&mut self, predicate: F)
+    where
+        F: Fn(&RawEvent) -> bool + Send + Sync + 'a,
+    {
+        self.predicates.push(Box::new(predicate));
+    }
+
+    /// Only returns events of type `E` for which `predicate` returns `true`.
+    ///
+    /// The events are decoded with [`RuntimeEvent::decode_raw`], skipping events of other
+    /// types and events which fail to decode. Can be called multiple times like
+    /// [`EventSubscription::filter`].
+    pub fn filter_decoded<E, F>(&mut self, predicate: F)
+    where
+        E: RuntimeEvent<T> + 'a,
+        F: Fn(&E) -> bool + Send + Sync + 'a,
+    {
+        self.filter(
+            move |raw| matches!(E::decode_raw(raw), Ok(Some(event)) if predicate(&event)),
+        );
+    }
+
+    /// Only returns events indexed under the given topic.
+    pub fn filter_topic(&mut self, topic: T::Hash) {
+        self.topic = Some(topic.encode());
+    }
+
+    fn matches(&self, event: &RawEvent) -> bool {
+        let type_filtered =
+            !self.event_filter.is_empty() || !self.module_filter.is_empty();
+        if type_filtered
+            && !self.module_filter.contains(&event.module.as_str())
+            && !self.event_filter.iter().any(|(module, variant)| {
+                event.module == *module && event.variant == *variant
+            })
+        {
+            return false
+        }
+        if let Some(topic) = self.topic.as_ref() {
+            if !event.topics.contains(topic) {
+                return false
+            }
+        }
+        self.predicates.iter().all(|predicate| predicate(event))
+    }
+
+    /// Skips events that can't be decoded instead of returning an error for the whole
+    /// block.
+    ///
+    /// See [`EventsDecoder::decode_events_lenient`].
+    pub fn skip_undecodable(&mut self) {
+        self.skip_undecodable = true;
     }
 
     /// Gets the next event.
     pub async fn next(&mut self) -> Option<Result<RawEvent, Error>> {
+        StreamExt::next(self).await
+    }
+
+    /// Queues the events of `change_set` which pass the filters.
+    fn push_events(
+        &mut self,
+        change_set: StorageChangeSet<T::Hash>,
+    ) -> Result<(), Error> {
+        if let Some(hash) = self.block.as_ref() {
+            if &change_set.block == hash {
+                self.finished = true;
+            } else {
+                return Ok(())
+            }
+        }
+        for (_key, data) in change_set.changes {
+            if let Some(data) = data {
+                let input = &mut &data.0[..];
+                let raw_events = if self.skip_undecodable {
+                    self.decoder.decode_events_lenient(input)?
+                } else {
+                    self.decoder.decode_events(input)?
+                };
+                for (phase, raw) in raw_events {
+                    if let Some(ext_index) = self.extrinsic {
+                        if phase != Phase::ApplyExtrinsic(ext_index as u32) {
+                            continue
+                        }
+                    }
+                    if let Some(filter) = self.phase.as_ref() {
+                        if &phase != filter {
+                            continue
+                        }
+                    }
+                    let event = match raw {
+                        Raw::Event(event) => event,
+                        Raw::Error(err) => return Err(err.into()),
+                        Raw::Undecodable { .. } => continue,
+                    };
+                    if self.matches(&event) {
+                        self.events.push_back(event);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, T: Runtime> Stream for EventSubscription<'a, T> {
+    type Item = Result<RawEvent, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         loop {
             if let Some(event) = self.events.pop_front() {
-                return Some(Ok(event))
+                return Poll::Ready(Some(Ok(event)))
             }
             if self.finished {
-                return None
+                return Poll::Ready(None)
             }
             // always return None if subscription has closed
-            let change_set = self.subscription.next().await?;
-            if let Some(hash) = self.block.as_ref() {
-                if &change_set.block == hash {
-                    self.finished = true;
-                } else {
-                    continue
-                }
-            }
-            for (_key, data) in change_set.changes {
-                if let Some(data) = data {
-                    let raw_events = match self.decoder.decode_events(&mut &data.0[..]) {
-                        Ok(events) => events,
-                        Err(error) => return Some(Err(error)),
-                    };
-                    for (phase, raw) in raw_events {
-                        if let Phase::ApplyExtrinsic(i) = phase {
-                            if let Some(ext_index) = self.extrinsic {
-                                if i as usize != ext_index {
-                                    continue
-                                }
-                            }
-                            let event = match raw {
-                                Raw::Event(event) => event,
-                                Raw::Error(err) => return Some(Err(err.into())),
-                            };
-                            if let Some((module, variant)) = self.event {
-                                if event.module != module || event.variant != variant {
-                                    continue
-                                }
-                            }
-                            self.events.push_back(event);
-                        }
-                    }
-                }
+            let change_set = match ready!(self.subscription.poll_next_unpin(cx)) {
+                Some(change_set) => change_set,
+                None => return Poll::Ready(None),
+            };
+            if let Err(err) = self.push_events(change_set) {
+                return Poll::Ready(Some(Err(err)))
             }
         }
     }
 }
 
+impl<'a, T: Runtime> Unpin for EventSubscription<'a, T> {}
+
 pub(crate) struct SystemEvents(StorageKey);
 
 impl SystemEvents {
@@ -160,6 +307,8 @@ pub struct FinalizedEventStorageSubscription<T: Runtime> {
     subscription: Subscription<T::Header>,
     storage_changes: VecDeque<StorageChangeSet<T::Hash>>,
     storage_key: StorageKey,
+    last_finalized: Option<T::BlockNumber>,
+    pending: Option<BoxFuture<'static, Result<Vec<StorageChangeSet<T::Hash>>, Error>>>,
 }
 
 impl<T: Runtime> FinalizedEventStorageSubscription<T> {
@@ -170,25 +319,594 @@ impl<T: Runtime> FinalizedEventStorageSubscription<T> {
             subscription,
             storage_changes: Default::default(),
             storage_key: SystemEvents::new().into(),
+            last_finalized: None,
+            pending: None,
         }
     }
 
     /// Gets the next change_set.
+    ///
+    /// If several blocks were finalized at once, the change sets of all of them are
+    /// returned in order.
     pub async fn next(&mut self) -> Option<StorageChangeSet<T::Hash>> {
+        StreamExt::next(self).await
+    }
+
+    /// Fetches the change sets of the blocks finalized after `last_finalized`, up to and
+    /// including `header`.
+    async fn finalized_storage_changes(
+        rpc: Rpc<T>,
+        storage_key: StorageKey,
+        last_finalized: Option<T::BlockNumber>,
+        header: T::Header,
+    ) -> Result<Vec<StorageChangeSet<T::Hash>>, Error> {
+        let mut hashes = match last_finalized {
+            Some(last_finalized) => {
+                Self::skipped_blocks(&rpc, last_finalized, &header).await?
+            }
+            None => Vec::new(),
+        };
+        hashes.push(header.hash());
+        let mut storage_changes = Vec::new();
+        for hash in hashes {
+            storage_changes.extend(
+                rpc.query_storage_at(&[storage_key.clone()], Some(hash))
+                    .await?,
+            );
+        }
+        Ok(storage_changes)
+    }
+
+    /// Returns the hashes of the blocks between `last_finalized` and `header`, in
+    /// ascending order, by walking back the parent hashes.
+    async fn skipped_blocks(
+        rpc: &Rpc<T>,
+        last_finalized: T::BlockNumber,
+        header: &T::Header,
+    ) -> Result<Vec<T::Hash>, Error> {
+        let mut hashes = Vec::new();
+        let mut number = *header.number();
+        let mut parent_hash = *header.parent_hash();
+        while number > last_finalized + 1u32.into() {
+            let parent = rpc.header(Some(parent_hash)).await?.ok_or_else(|| {
+                Error::Other(format!("Block {:?} not found", parent_hash))
+            })?;
+            hashes.push(parent_hash);
+            number = *parent.number();
+            parent_hash = *parent.parent_hash();
+        }
+        hashes.reverse();
+        Ok(hashes)
+    }
+}
+
+impl<T: Runtime> Stream for FinalizedEventStorageSubscription<T> {
+    type Item = StorageChangeSet<T::Hash>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
         loop {
             if let Some(storage_change) = self.storage_changes.pop_front() {
-                return Some(storage_change)
+                return Poll::Ready(Some(storage_change))
+            }
+            if let Some(pending) = self.pending.as_mut() {
+                let storage_changes = ready!(pending.poll_unpin(cx));
+                self.pending = None;
+                match storage_changes {
+                    Ok(storage_changes) => self.storage_changes.extend(storage_changes),
+                    Err(err) => {
+                        log::error!(
+                            "Fetching finalized events failed: {:?} dropping",
+                            err
+                        );
+                        return Poll::Ready(None)
+                    }
+                }
+                continue
+            }
+            let notif = ready!(self.subscription.poll_next_unpin(cx));
+            let header = match subscription_item("HeaderSubscription", notif) {
+                Some(header) => header,
+                None => return Poll::Ready(None),
+            };
+            let last_finalized = self.last_finalized.replace(*header.number());
+            let pending = Self::finalized_storage_changes(
+                self.rpc.clone(),
+                self.storage_key.clone(),
+                last_finalized,
+                header,
+            );
+            self.pending = Some(pending.boxed());
+        }
+    }
+}
+
+impl<T: Runtime> Unpin for FinalizedEventStorageSubscription<T> {}
+
+/// Subscription to the changes of one or more storage values of the same `Store`.
+pub struct StoreSubscription<T: Runtime, R: Decode> {
+    subscription: Subscription<StorageChangeSet<T::Hash>>,
+    keys: Vec<StorageKey>,
+    changes: VecDeque<(T::Hash, usize, Option<StorageData>)>,
+    marker: PhantomData<fn() -> R>,
+}
+
+impl<T: Runtime, R: Decode> StoreSubscription<T, R> {
+    /// Creates a new store subscription for the values under `keys`.
+    pub(crate) fn new(
+        subscription: Subscription<StorageChangeSet<T::Hash>>,
+        keys: Vec<StorageKey>,
+    ) -> Self {
+        Self {
+            subscription,
+            keys,
+            changes: Default::default(),
+            marker: PhantomData,
+        }
+    }
+
+    /// Gets the next value, along with the hash of the block it was changed in.
+    pub async fn next(&mut self) -> Option<Result<(T::Hash, Option<R>), Error>> {
+        StreamExt::next(self).await
+    }
+
+    /// Gets the next value, along with the hash of the block it was changed in and the
+    /// index of its store in the stores the subscription was created for.
+    pub async fn next_with_index(
+        &mut self,
+    ) -> Option<Result<(T::Hash, usize, Option<R>), Error>> {
+        future::poll_fn(|cx| self.poll_next_with_index(cx)).await
+    }
+
+    fn poll_next_with_index(
+        &mut self,
+        cx: &mut Context,
+    ) -> Poll<Option<Result<(T::Hash, usize, Option<R>), Error>>> {
+        loop {
+            if let Some((block, index, data)) = self.changes.pop_front() {
+                let value = data
+                    .map(|data| R::decode(&mut &data.0[..]))
+                    .transpose()
+                    .map_err(Into::into);
+                return Poll::Ready(Some(value.map(|value| (block, index, value))))
+            }
+            let notif = ready!(self.subscription.poll_next_unpin(cx));
+            let change_set = match subscription_item("StorageSubscription", notif) {
+                Some(change_set) => change_set,
+                None => return Poll::Ready(None),
+            };
+            for (key, data) in change_set.changes {
+                if let Some(index) = self.keys.iter().position(|k| k == &key) {
+                    self.changes.push_back((change_set.block, index, data));
+                }
+            }
+        }
+    }
+}
+
+impl<T: Runtime, R: Decode> Stream for StoreSubscription<T, R> {
+    type Item = Result<(T::Hash, Option<R>), Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let change = ready!(self.poll_next_with_index(cx));
+        Poll::Ready(change.map(|change| change.map(|(block, _, value)| (block, value))))
+    }
+}
+
+impl<T: Runtime, R: Decode> Unpin for StoreSubscription<T, R> {}
+
+/// Change of the best chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum BlockChange<Hash> {
+    /// The block became part of the best chain.
+    Enacted(Hash),
+    /// The block was removed from the best chain by a reorg.
+    Retracted(Hash),
+}
+
+/// Best block subscription that reports which blocks were added to and removed from the
+/// best chain.
+///
+/// When a fork becomes the best chain, the blocks of the previous best chain down to the
+/// common ancestor are reported as `Retracted` (highest first), followed by the blocks of
+/// the new best chain as `Enacted` (lowest first).
+pub struct BestBlockSubscription<T: Runtime> {
+    /// `None` while the pending future owns it.
+    state: Option<Box<BestBlockState<T>>>,
+    pending: Option<
+        BoxFuture<
+            'static,
+            (
+                Box<BestBlockState<T>>,
+                Option<Result<BlockChange<T::Hash>, Error>>,
+            ),
+        >,
+    >,
+}
+
+impl<T: Runtime> BestBlockSubscription<T> {
+    /// Creates a new best block subscription.
+    pub(crate) fn new(rpc: Rpc<T>, subscription: Subscription<T::Header>) -> Self {
+        let state = BestBlockState {
+            rpc,
+            subscription,
+            headers: HashMap::new(),
+            best: None,
+            changes: Default::default(),
+        };
+        Self {
+            state: Some(Box::new(state)),
+            pending: None,
+        }
+    }
+
+    /// Gets the next change of the best chain.
+    pub async fn next(&mut self) -> Option<Result<BlockChange<T::Hash>, Error>> {
+        StreamExt::next(self).await
+    }
+}
+
+impl<T: Runtime> Stream for BestBlockSubscription<T> {
+    type Item = Result<BlockChange<T::Hash>, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let state = &mut this.state;
+        let pending = this.pending.get_or_insert_with(|| {
+            let mut state = state.take().expect("only taken by the pending future; qed");
+            async move {
+                let change = state.next().await;
+                (state, change)
+            }
+            .boxed()
+        });
+        let (state, change) = ready!(pending.poll_unpin(cx));
+        this.pending = None;
+        this.state = Some(state);
+        Poll::Ready(change)
+    }
+}
+
+impl<T: Runtime> Unpin for BestBlockSubscription<T> {}
+
+/// State of a [`BestBlockSubscription`], moved into the future fetching the next change.
+struct BestBlockState<T: Runtime> {
+    rpc: Rpc<T>,
+    subscription: Subscription<T::Header>,
+    headers: HashMap<T::Hash, T::Header>,
+    best: Option<T::Header>,
+    changes: VecDeque<BlockChange<T::Hash>>,
+}
+
+impl<T: Runtime> BestBlockState<T> {
+    /// Number of blocks below the best block for which headers are kept.
+    const PRUNING_DEPTH: u32 = 256;
+
+    async fn next(&mut self) -> Option<Result<BlockChange<T::Hash>, Error>> {
+        loop {
+            if let Some(change) = self.changes.pop_front() {
+                return Some(Ok(change))
             }
             let header: T::Header =
                 read_subscription_response("HeaderSubscription", &mut self.subscription)
                     .await?;
-            self.storage_changes.extend(
-                self.rpc
-                    .query_storage_at(&[self.storage_key.clone()], Some(header.hash()))
-                    .await
-                    .ok()?,
-            );
+            if let Err(err) = self.import(header).await {
+                return Some(Err(err))
+            }
+        }
+    }
+
+    async fn import(&mut self, header: T::Header) -> Result<(), Error> {
+        let hash = header.hash();
+        let number = *header.number();
+        self.headers.insert(hash, header.clone());
+        let best = match self.best.clone() {
+            Some(best) => best,
+            None => {
+                self.best = Some(header);
+                self.changes.push_back(BlockChange::Enacted(hash));
+                return Ok(())
+            }
+        };
+
+        // walk back both chains to the common ancestor, keeping the previous best block
+        // if that fails
+        let mut retracted = best;
+        let mut enacted = header.clone();
+        let mut retracted_hashes = Vec::new();
+        let mut enacted_hashes = Vec::new();
+        while retracted.hash() != enacted.hash() {
+            if retracted.number() >= enacted.number() {
+                retracted_hashes.push(retracted.hash());
+                retracted = self.header(*retracted.parent_hash()).await?;
+            } else {
+                enacted_hashes.push(enacted.hash());
+                enacted = self.header(*enacted.parent_hash()).await?;
+            }
         }
+        self.changes
+            .extend(retracted_hashes.into_iter().map(BlockChange::Retracted));
+        self.changes
+            .extend(enacted_hashes.into_iter().rev().map(BlockChange::Enacted));
+        self.best = Some(header);
+
+        let pruned = number.saturating_sub(Self::PRUNING_DEPTH.into());
+        self.headers.retain(|_, header| *header.number() >= pruned);
+        Ok(())
+    }
+
+    async fn header(&mut self, hash: T::Hash) -> Result<T::Header, Error> {
+        if let Some(header) = self.headers.get(&hash) {
+            return Ok(header.clone())
+        }
+        let header = self
+            .rpc
+            .header(Some(hash))
+            .await?
+            .ok_or_else(|| Error::Other(format!("Block {:?} not found", hash)))?;
+        self.headers.insert(hash, header.clone());
+        Ok(header)
+    }
+}
+
+/// Event subscription that reconnects when the connection is lost.
+///
+/// After reconnecting, the events of the blocks between the last delivered block and the
+/// first block of the new subscription are fetched, so no block is skipped. The
+/// subscription ends after `max_reconnect_attempts` failed attempts in a row.
+pub struct ReconnectingEventStorageSubscription<T: Runtime> {
+    /// `None` while the pending future owns it.
+    state: Option<Box<ReconnectingState<T>>>,
+    pending: Option<
+        BoxFuture<
+            'static,
+            (Box<ReconnectingState<T>>, Option<StorageChangeSet<T::Hash>>),
+        >,
+    >,
+    reconnect_delay: Duration,
+    max_reconnect_attempts: u32,
+}
+
+impl<T: Runtime> ReconnectingEventStorageSubscription<T> {
+    /// Creates a new reconnecting event storage subscription.
+    pub(crate) fn new(
+        finalized: bool,
+        rpc: Rpc<T>,
+        subscription: EventStorageSubscription<T>,
+    ) -> Self {
+        let state = ReconnectingState {
+            finalized,
+            rpc,
+            subscription: Some(subscription),
+            last_block: None,
+            reconnected: false,
+            failed_attempts: 0,
+            storage_changes: Default::default(),
+            storage_key: SystemEvents::new().into(),
+        };
+        Self {
+            state: Some(Box::new(state)),
+            pending: None,
+            reconnect_delay: Duration::from_secs(1),
+            max_reconnect_attempts: 10,
+        }
+    }
+
+    /// Sets the delay between failed reconnection attempts, one second by default.
+    pub fn set_reconnect_delay(&mut self, delay: Duration) {
+        self.reconnect_delay = delay;
+    }
+
+    /// Sets the number of failed reconnection attempts in a row after which the
+    /// subscription ends, 10 by default.
+    pub fn set_max_reconnect_attempts(&mut self, attempts: u32) {
+        self.max_reconnect_attempts = attempts;
+    }
+
+    /// Gets the next change_set, reconnecting if the connection was lost.
+    ///
+    /// Returns `None` once reconnecting failed `max_reconnect_attempts` times in a row.
+    pub async fn next(&mut self) -> Option<StorageChangeSet<T::Hash>> {
+        StreamExt::next(self).await
+    }
+}
+
+impl<T: Runtime> Stream for ReconnectingEventStorageSubscription<T> {
+    type Item = StorageChangeSet<T::Hash>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        let this = &mut *self;
+        let state = &mut this.state;
+        let reconnect_delay = this.reconnect_delay;
+        let max_reconnect_attempts = this.max_reconnect_attempts;
+        let pending = this.pending.get_or_insert_with(|| {
+            let mut state = state.take().expect("only taken by the pending future; qed");
+            async move {
+                let storage_change =
+                    state.next(reconnect_delay, max_reconnect_attempts).await;
+                (state, storage_change)
+            }
+            .boxed()
+        });
+        let (state, storage_change) = ready!(pending.poll_unpin(cx));
+        this.pending = None;
+        this.state = Some(state);
+        Poll::Ready(storage_change)
+    }
+}
+
+impl<T: Runtime> Unpin for ReconnectingEventStorageSubscription<T> {}
+
+/// State of a [`ReconnectingEventStorageSubscription`], moved into the future fetching the
+/// next change set.
+struct ReconnectingState<T: Runtime> {
+    finalized: bool,
+    rpc: Rpc<T>,
+    subscription: Option<EventStorageSubscription<T>>,
+    last_block: Option<T::Hash>,
+    reconnected: bool,
+    /// Failed reconnection attempts since the last successful reconnect.
+    failed_attempts: u32,
+    storage_changes: VecDeque<StorageChangeSet<T::Hash>>,
+    storage_key: StorageKey,
+}
+
+impl<T: Runtime> ReconnectingState<T> {
+    async fn next(
+        &mut self,
+        reconnect_delay: Duration,
+        max_attempts: u32,
+    ) -> Option<StorageChangeSet<T::Hash>> {
+        loop {
+            if let Some(storage_change) = self.storage_changes.pop_front() {
+                self.last_block = Some(storage_change.block);
+                return Some(storage_change)
+            }
+            if self.subscription.is_none() {
+                self.reconnect(reconnect_delay, max_attempts).await?;
+            }
+            let storage_change = match self.subscription.as_mut() {
+                Some(subscription) => subscription.next().await,
+                None => continue,
+            };
+            let storage_change = match storage_change {
+                Some(storage_change) => storage_change,
+                None => {
+                    log::warn!("Event subscription closed, reconnecting");
+                    if self.reconnected {
+                        self.failed_attempts += 1;
+                    }
+                    self.subscription = None;
+                    continue
+                }
+            };
+            if self.last_block == Some(storage_change.block) {
+                continue
+            }
+            if self.reconnected {
+                let missed = match self.last_block {
+                    Some(last) => {
+                        Self::missed_storage_changes(
+                            &self.rpc,
+                            &self.storage_key,
+                            last,
+                            storage_change.block,
+                        )
+                        .await
+                    }
+                    None => Ok(Vec::new()),
+                };
+                match missed {
+                    Ok(missed) => {
+                        self.reconnected = false;
+                        self.failed_attempts = 0;
+                        self.storage_changes.extend(missed);
+                    }
+                    Err(err) => {
+                        log::warn!("Failed to fetch missed events: {}", err);
+                        self.failed_attempts += 1;
+                        self.subscription = None;
+                        continue
+                    }
+                }
+            }
+            self.storage_changes.push_back(storage_change);
+        }
+    }
+
+    /// Subscribes again, returning `None` once `max_attempts` attempts failed in a row.
+    async fn reconnect(
+        &mut self,
+        reconnect_delay: Duration,
+        max_attempts: u32,
+    ) -> Option<()> {
+        loop {
+            if self.failed_attempts >= max_attempts {
+                log::error!(
+                    "Giving up reconnecting after {} failed attempts",
+                    self.failed_attempts
+                );
+                return None
+            }
+            if self.failed_attempts > 0 {
+                Delay::new(reconnect_delay).await;
+            }
+            match Self::subscribe(&self.rpc, self.finalized).await {
+                Ok(subscription) => {
+                    self.subscription = Some(subscription);
+                    self.reconnected = true;
+                    return Some(())
+                }
+                Err(err) => {
+                    log::warn!("Reconnecting failed: {}", err);
+                    self.failed_attempts += 1;
+                }
+            }
+        }
+    }
+
+    /// Reconnects the rpc client, which is shared with the `Client`, and subscribes again.
+    async fn subscribe(
+        rpc: &Rpc<T>,
+        finalized: bool,
+    ) -> Result<EventStorageSubscription<T>, Error> {
+        rpc.client.reconnect().await?;
+        if finalized {
+            rpc.subscribe_finalized_events().await
+        } else {
+            rpc.subscribe_events().await
+        }
+    }
+
+    /// Fetches the events of the blocks after `last` and before `next`, walking back the
+    /// parents of `next` so blocks of other forks are skipped.
+    ///
+    /// Fails if `last` is not an ancestor of `next`. Takes the fields it needs instead of
+    /// `&self`, which isn't `Sync`, so the future stays `Send`.
+    async fn missed_storage_changes(
+        rpc: &Rpc<T>,
+        storage_key: &StorageKey,
+        last: T::Hash,
+        next: T::Hash,
+    ) -> Result<Vec<StorageChangeSet<T::Hash>>, Error> {
+        let not_ancestor = || {
+            Error::Other(format!(
+                "Block {:?} is not an ancestor of block {:?}",
+                last, next
+            ))
+        };
+        let last_number = *Self::header(rpc, last).await?.number();
+        let mut header = Self::header(rpc, next).await?;
+        if *header.number() <= last_number {
+            return Err(not_ancestor())
+        }
+        let mut hashes = Vec::new();
+        while *header.number() > last_number + 1u32.into() {
+            hashes.push(*header.parent_hash());
+            header = Self::header(rpc, *header.parent_hash()).await?;
+        }
+        if *header.parent_hash() != last {
+            return Err(not_ancestor())
+        }
+        hashes.reverse();
+
+        let data = rpc.storage_at_blocks(storage_key, &hashes).await?;
+        let storage_changes = hashes
+            .into_iter()
+            .zip(data)
+            .map(|(block, data)| {
+                StorageChangeSet {
+                    block,
+                    changes: vec![(storage_key.clone(), data)],
+                }
+            })
+            .collect();
+        Ok(storage_changes)
+    }
+
+    async fn header(rpc: &Rpc<T>, hash: T::Hash) -> Result<T::Header, Error> {
+        rpc.header(Some(hash))
+            .await?
+            .ok_or_else(|| Error::Other(format!("Block {:?} not found", hash)))
     }
 }
 
@@ -198,17 +916,28 @@ pub enum EventStorageSubscription<T: Runtime> {
     Imported(Subscription<StorageChangeSet<T::Hash>>),
     /// Events that are Finalized
     Finalized(FinalizedEventStorageSubscription<T>),
+    /// Events of a subscription that reconnects when the connection is lost
+    Reconnecting(Box<ReconnectingEventStorageSubscription<T>>),
 }
 
 impl<T: Runtime> EventStorageSubscription<T> {
     /// Gets the next change_set from the subscription.
     pub async fn next(&mut self) -> Option<StorageChangeSet<T::Hash>> {
-        match self {
+        StreamExt::next(self).await
+    }
+}
+
+impl<T: Runtime> Stream for EventStorageSubscription<T> {
+    type Item = StorageChangeSet<T::Hash>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        match &mut *self {
             Self::Imported(event_sub) => {
-                read_subscription_response("StorageChangeSetSubscription", event_sub)
-                    .await
+                let notif = ready!(event_sub.poll_next_unpin(cx));
+                Poll::Ready(subscription_item("StorageChangeSetSubscription", notif))
             }
-            Self::Finalized(event_sub) => event_sub.next().await,
+            Self::Finalized(event_sub) => event_sub.poll_next_unpin(cx),
+            Self::Reconnecting(event_sub) => event_sub.poll_next_unpin(cx),
         }
     }
 }
@@ -220,12 +949,229 @@ async fn read_subscription_response<T>(
 where
     T: DeserializeOwned,
 {
-    match sub.next().await {
-        Ok(Some(next)) => Some(next),
-        Ok(None) => None,
-        Err(e) => {
+    subscription_item(sub_name, StreamExt::next(sub).await)
+}
+
+/// Returns the notification, or `None` if the subscription was closed or failed.
+fn subscription_item<T>(sub_name: &str, notif: Option<Result<T, Error>>) -> Option<T> {
+    match notif {
+        Some(Ok(next)) => Some(next),
+        Some(Err(e)) => {
             log::error!("Subscription {} failed: {:?} dropping", sub_name, e);
             None
         }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        frame::system::System,
+        rpc::{
+            ReplayTransport,
+            RpcClient,
+            TraceEntry,
+        },
+    };
+    use jsonrpsee_types::JsonValue;
+
+    type TestRuntime = crate::NodeTemplateRuntime;
+    type TestHeader = <TestRuntime as System>::Header;
+
+    /// Returns a chain of `count` headers, starting at block 1.
+    fn headers(count: u32) -> Vec<TestHeader> {
+        let mut parent_hash = Default::default();
+        (1..=count)
+            .map(|number| {
+                let header = TestHeader::new(
+                    number,
+                    Default::default(),
+                    Default::default(),
+                    parent_hash,
+                    Default::default(),
+                );
+                parent_hash = header.hash();
+                header
+            })
+            .collect()
+    }
+
+    /// Returns a child of `parent`, with a state root distinguishing the children of
+    /// different forks.
+    fn child(parent: &TestHeader, fork: u8) -> TestHeader {
+        TestHeader::new(
+            parent.number + 1,
+            Default::default(),
+            [fork; 32].into(),
+            parent.hash(),
+            Default::default(),
+        )
+    }
+
+    fn subscribe_events(
+        key: &StorageKey,
+        change_sets: &[StorageChangeSet<<TestRuntime as System>::Hash>],
+    ) -> TraceEntry {
+        TraceEntry::Subscription {
+            method: "state_subscribeStorage".into(),
+            params: vec![serde_json::to_value(Some(vec![key.clone()])).unwrap()],
+            notifications: change_sets
+                .iter()
+                .map(|change_set| serde_json::to_value(change_set).unwrap())
+                .collect(),
+        }
+    }
+
+    fn request<R: serde::Serialize>(
+        method: &str,
+        params: Vec<JsonValue>,
+        result: R,
+    ) -> TraceEntry {
+        TraceEntry::Request {
+            method: method.into(),
+            params,
+            result: Some(serde_json::to_value(result).unwrap()),
+            error: None,
+        }
+    }
+
+    #[async_std::test]
+    async fn test_reconnecting_back_fills_missed_blocks() {
+        let headers = headers(4);
+        let hash = |number: usize| headers[number - 1].hash();
+        let key: StorageKey = SystemEvents::new().into();
+        let data = |number: u8| Some(StorageData(vec![number]));
+        let change_set = |number: usize| {
+            StorageChangeSet {
+                block: hash(number),
+                changes: vec![(key.clone(), data(number as u8))],
+            }
+        };
+        let hash_param = |number: usize| serde_json::to_value(hash(number)).unwrap();
+        let mut trace = vec![
+            // the first subscription closes after block 1, the second starts at block 4
+            subscribe_events(&key, &[change_set(1)]),
+            subscribe_events(&key, &[change_set(4)]),
+        ];
+        for number in &[1, 4, 3, 2] {
+            trace.push(request(
+                "chain_getHeader",
+                vec![hash_param(*number)],
+                &headers[*number - 1],
+            ));
+        }
+        for number in 2..=3 {
+            trace.push(request(
+                "state_getStorage",
+                vec![serde_json::to_value(&key).unwrap(), hash_param(number)],
+                data(number as u8),
+            ));
+        }
+
+        let rpc = Rpc::<TestRuntime>::new(RpcClient::new(ReplayTransport::new(trace)));
+        let subscription = rpc.subscribe_events().await.unwrap();
+        let subscription =
+            ReconnectingEventStorageSubscription::new(false, rpc, subscription);
+        let change_sets = subscription.take(4).collect::<Vec<_>>().await;
+        assert_eq!(
+            change_sets,
+            vec![change_set(1), change_set(2), change_set(3), change_set(4)]
+        );
+    }
+
+    #[async_std::test]
+    async fn test_reconnecting_ends_if_last_block_is_not_an_ancestor() {
+        let a = headers(2);
+        let b2 = child(&a[0], 1);
+        let b3 = child(&b2, 1);
+        let key: StorageKey = SystemEvents::new().into();
+        let change_set = |header: &TestHeader| {
+            StorageChangeSet {
+                block: header.hash(),
+                changes: vec![(key.clone(), None)],
+            }
+        };
+        let header = |header: &TestHeader| {
+            request(
+                "chain_getHeader",
+                vec![serde_json::to_value(header.hash()).unwrap()],
+                header,
+            )
+        };
+        let trace = vec![
+            // the second subscription starts at block 3 of another fork
+            subscribe_events(&key, &[change_set(&a[0]), change_set(&a[1])]),
+            subscribe_events(&key, &[change_set(&b3)]),
+            header(&a[1]),
+            header(&b3),
+        ];
+
+        let rpc = Rpc::<TestRuntime>::new(RpcClient::new(ReplayTransport::new(trace)));
+        let subscription = rpc.subscribe_events().await.unwrap();
+        let mut subscription =
+            ReconnectingEventStorageSubscription::new(false, rpc, subscription);
+        subscription.set_reconnect_delay(Duration::from_millis(0));
+        subscription.set_max_reconnect_attempts(2);
+        let change_sets = subscription.collect::<Vec<_>>().await;
+        assert_eq!(change_sets, vec![change_set(&a[0]), change_set(&a[1])]);
+    }
+
+    #[async_std::test]
+    async fn test_best_block_reorg() {
+        let a = headers(3);
+        let b2 = child(&a[0], 1);
+        let b3 = child(&b2, 1);
+        let b4 = child(&b3, 1);
+        // the parent of the orphan can't be fetched
+        let mut orphan = child(&a[2], 2);
+        orphan.parent_hash = [2; 32].into();
+        let notifications = vec![&a[0], &a[1], &a[2], &orphan, &b4];
+        let trace = vec![
+            TraceEntry::Subscription {
+                method: "chain_subscribeNewHeads".into(),
+                params: Vec::new(),
+                notifications: notifications
+                    .into_iter()
+                    .map(|header| serde_json::to_value(header).unwrap())
+                    .collect(),
+            },
+            request(
+                "chain_getHeader",
+                vec![serde_json::to_value(b3.hash()).unwrap()],
+                &b3,
+            ),
+            request(
+                "chain_getHeader",
+                vec![serde_json::to_value(b2.hash()).unwrap()],
+                &b2,
+            ),
+        ];
+
+        let rpc = Rpc::<TestRuntime>::new(RpcClient::new(ReplayTransport::new(trace)));
+        let subscription = rpc.subscribe_blocks().await.unwrap();
+        let mut subscription = BestBlockSubscription::new(rpc, subscription);
+        let mut changes = Vec::new();
+        for _ in 0..3 {
+            changes.push(subscription.next().await.unwrap().unwrap());
+        }
+        assert!(subscription.next().await.unwrap().is_err());
+        while let Some(change) = subscription.next().await {
+            changes.push(change.unwrap());
+        }
+        assert_eq!(
+            changes,
+            vec![
+                BlockChange::Enacted(a[0].hash()),
+                BlockChange::Enacted(a[1].hash()),
+                BlockChange::Enacted(a[2].hash()),
+                BlockChange::Retracted(a[2].hash()),
+                BlockChange::Retracted(a[1].hash()),
+                BlockChange::Enacted(b2.hash()),
+                BlockChange::Enacted(b3.hash()),
+                BlockChange::Enacted(b4.hash()),
+            ]
+        );
     }
 }
@@ -119,6 +119,41 @@ async fn test_getting_block() {
     client.block(block_hash).await.unwrap();
 }
 
+#[async_std::test]
+async fn test_events_at() {
+    let signer = PairSigner::new(AccountKeyring::Alice.pair());
+    let dest = AccountKeyring::Bob.to_account_id().into();
+
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    let result = client
+        .watch(
+            balances::TransferCall {
+                to: &dest,
+                amount: 10_000,
+            },
+            &signer,
+        )
+        .await
+        .unwrap();
+
+    let events = client.events_at(result.block).await.unwrap();
+    assert!(events.iter().any(|(_, raw)| {
+        match raw {
+            Raw::Event(event) => {
+                event.module == "Balances" && event.variant == "Transfer"
+            }
+            _ => false,
+        }
+    }));
+
+    let events = client.events_range(0, 1).await.unwrap();
+    assert_eq!(events.len(), 2);
+    let lenient = client.events_range_lenient(0, 1).await.unwrap();
+    assert_eq!(lenient.len(), 2);
+    assert_eq!(lenient[1].0, events[1].0);
+}
+
 #[async_std::test]
 async fn test_getting_read_proof() {
     let node_process = test_node_process().await;
@@ -136,6 +171,15 @@ async fn test_getting_read_proof() {
         .unwrap();
 }
 
+#[async_std::test]
+async fn test_validate_modules() {
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    if let Err(errors) = client.validate_modules() {
+        panic!("module definitions don't match the metadata: {:#?}", errors);
+    }
+}
+
 #[async_std::test]
 async fn test_chain_subscribe_blocks() {
     let node_process = test_node_process().await;
@@ -144,6 +188,29 @@ async fn test_chain_subscribe_blocks() {
     blocks.next().await.unwrap();
 }
 
+#[async_std::test]
+async fn test_chain_subscribe_blocks_stream() {
+    use futures::{
+        StreamExt,
+        TryStreamExt,
+    };
+
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    let blocks = client.subscribe_blocks().await.unwrap();
+    let headers = blocks.take(2).try_collect::<Vec<_>>().await.unwrap();
+    assert_eq!(headers.len(), 2);
+}
+
+#[async_std::test]
+async fn test_chain_subscribe_best_blocks() {
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    let mut blocks = client.subscribe_best_blocks().await.unwrap();
+    let change = blocks.next().await.unwrap().unwrap();
+    assert!(matches!(change, BlockChange::Enacted(_)));
+}
+
 #[async_std::test]
 async fn test_chain_subscribe_finalized_blocks() {
     let node_process = test_node_process().await;
@@ -152,6 +219,182 @@ async fn test_chain_subscribe_finalized_blocks() {
     blocks.next().await.unwrap();
 }
 
+#[async_std::test]
+async fn test_finalized_events_stream() {
+    use futures::StreamExt;
+
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    let sub = client.subscribe_finalized_events().await.unwrap();
+    let mut events = EventSubscription::<TestRuntime>::new(sub, client.events_decoder());
+    events.filter_module("System");
+    let events = events.take(2).collect::<Vec<_>>().await;
+    assert_eq!(events.len(), 2);
+    for event in events {
+        assert_eq!(event.unwrap().module, "System");
+    }
+}
+
+#[async_std::test]
+async fn test_subscribe_store() {
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    let account_id = AccountKeyring::Alice.to_account_id();
+    let mut sub = client
+        .subscribe_store(&system::AccountStore {
+            account_id: &account_id,
+        })
+        .await
+        .unwrap();
+    let (_, info) = sub.next().await.unwrap().unwrap();
+    assert_ne!(info.unwrap().data.free, 0);
+}
+
+#[async_std::test]
+async fn test_fetch_many() {
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    let alice = AccountKeyring::Alice.to_account_id();
+    let bob = AccountKeyring::Bob.to_account_id();
+    let stores = [
+        system::AccountStore { account_id: &alice },
+        system::AccountStore { account_id: &bob },
+    ];
+    let infos = client.fetch_many(&stores, None).await.unwrap();
+    assert_eq!(infos.len(), 2);
+    for (store, info) in stores.iter().zip(infos) {
+        assert_eq!(info, client.fetch(store, None).await.unwrap());
+    }
+}
+
+#[async_std::test]
+async fn test_getting_headers() {
+    let node_process = test_node_process().await;
+    let client = node_process.client();
+    let genesis_hash = client.block_hash(Some(0u32.into())).await.unwrap().unwrap();
+    let best_hash = client.block_hash(None).await.unwrap().unwrap();
+    let headers = client.headers(&[best_hash, genesis_hash]).await.unwrap();
+    assert_eq!(headers[0], client.header(Some(best_hash)).await.unwrap());
+    assert_eq!(headers[1].as_ref().unwrap().number, 0);
+}
+
+#[async_std::test]
+async fn test_failover_client() {
+    let node_process = test_node_process().await;
+    let urls = ["ws://127.0.0.1:1", node_process.url()];
+    let client = FailoverClientBuilder::new(urls.iter().copied())
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(client.active_url(), Some(node_process.url()));
+
+    let client = ClientBuilder::<TestRuntime>::new()
+        .set_urls(urls.iter().copied())
+        .build()
+        .await
+        .unwrap();
+    client.block_hash(None).await.unwrap();
+    client
+        .subscribe_blocks()
+        .await
+        .unwrap()
+        .next()
+        .await
+        .unwrap();
+
+    let result = ClientBuilder::<TestRuntime>::new()
+        .set_url(node_process.url())
+        .set_urls(urls.iter().copied())
+        .build()
+        .await;
+    assert!(result.is_err());
+}
+
+#[async_std::test]
+async fn test_replay_client() {
+    let dir = tempdir::TempDir::new("subxt-trace").unwrap();
+    let path = dir.path().join("trace.json");
+    let alice = AccountKeyring::Alice.to_account_id();
+    let store = system::AccountStore { account_id: &alice };
+
+    let node_process = test_node_process().await;
+    let recorder =
+        RecordingTransport::new(RpcClient::connect(node_process.url()).await.unwrap());
+    let client = ClientBuilder::<TestRuntime>::new()
+        .set_client(recorder.clone())
+        .build()
+        .await
+        .unwrap();
+    let hash = client.block_hash(None).await.unwrap();
+    let info = client.fetch(&store, hash).await.unwrap();
+    let header = client
+        .subscribe_blocks()
+        .await
+        .unwrap()
+        .next()
+        .await
+        .unwrap();
+    recorder.save(&path).unwrap();
+    drop(node_process);
+
+    let client = ClientBuilder::<TestRuntime>::new()
+        .set_client(ReplayTransport::load(&path).unwrap())
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(client.block_hash(None).await.unwrap(), hash);
+    assert_eq!(client.fetch(&store, hash).await.unwrap(), info);
+    let mut blocks = client.subscribe_blocks().await.unwrap();
+    assert_eq!(blocks.next().await.unwrap(), header);
+    assert_eq!(blocks.next().await.unwrap(), None);
+}
+
+#[async_std::test]
+async fn test_build_offline() {
+    use codec::Encode;
+    use frame_metadata::{
+        decode_different::DecodeDifferent,
+        v13::{
+            ExtrinsicMetadata,
+            RuntimeMetadataV13,
+        },
+        RuntimeMetadata,
+        RuntimeMetadataPrefixed,
+        META_RESERVED,
+    };
+
+    let blob = RuntimeMetadataPrefixed(
+        META_RESERVED,
+        RuntimeMetadata::V13(RuntimeMetadataV13 {
+            modules: DecodeDifferent::Decoded(vec![]),
+            extrinsic: ExtrinsicMetadata {
+                version: 4,
+                signed_extensions: vec![],
+            },
+        }),
+    )
+    .encode();
+    let genesis_hash = sp_core::H256::repeat_byte(1);
+    let runtime_version = RuntimeVersion {
+        spec_version: 7,
+        ..Default::default()
+    };
+
+    // no node is running, and the transport has nothing to replay
+    let client = ClientBuilder::<TestRuntime>::new()
+        .set_client(ReplayTransport::new(Vec::new()))
+        .set_metadata(Metadata::from_encoded(&blob).unwrap())
+        .set_genesis_hash(genesis_hash)
+        .set_runtime_version(runtime_version.clone())
+        .build()
+        .await
+        .unwrap();
+    assert_eq!(client.metadata().encoded(), &blob[..]);
+    assert_eq!(client.genesis(), &genesis_hash);
+    assert_eq!(client.runtime_version(), &runtime_version);
+    assert!(client.block_hash(None).await.is_err());
+}
+
 #[async_std::test]
 async fn test_fetch_keys() {
     let node_process = test_node_process().await;
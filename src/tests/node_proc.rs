@@ -39,6 +39,7 @@ use std::{
 pub struct TestNodeProcess<R: Runtime> {
     proc: process::Child,
     client: Client<R>,
+    url: String,
 }
 
 impl<R> Drop for TestNodeProcess<R>
@@ -81,6 +82,11 @@ where
     pub fn client(&self) -> &Client<R> {
         &self.client
     }
+
+    /// Returns the WebSocket url of the running node.
+    pub fn url(&self) -> &str {
+        &self.url
+    }
 }
 
 /// Construct a test node process.
@@ -181,7 +187,13 @@ impl TestNodeProcessBuilder {
             }
         };
         match client {
-            Ok(client) => Ok(TestNodeProcess { proc, client }),
+            Ok(client) => {
+                Ok(TestNodeProcess {
+                    proc,
+                    client,
+                    url: ws_url,
+                })
+            }
             Err(err) => {
                 let err = format!(
                     "Failed to connect to node rpc at {} after {} attempts: {}",
@@ -0,0 +1,303 @@
+// Copyright 2019-2021 Parity Technologies (UK) Ltd.
+// This file is part of substrate-subxt.
+//
+// subxt is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// subxt is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with substrate-subxt.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Dynamic representation of SCALE encoded values, decoded from metadata type names.
+
+use codec::{
+    Compact,
+    Decode,
+    Error as CodecError,
+};
+use sp_runtime::{
+    PerThing,
+    Perbill,
+    Percent,
+    Permill,
+    Perquintill,
+};
+use std::{
+    any::TypeId,
+    fmt,
+};
+
+/// A value decoded without a concrete Rust type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Value {
+    /// Unnamed fields, e.g. event arguments or a tuple.
+    Composite(Vec<Value>),
+    /// An enum variant with its fields, e.g. `Some(_)` or `Ok(_)`.
+    Variant(String, Vec<Value>),
+    /// A sequence of values of the same type.
+    Sequence(Vec<Value>),
+    /// A primitive value.
+    Primitive(Primitive),
+}
+
+/// A call decoded without a concrete Rust type.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct DecodedCall {
+    /// The name of the module the call belongs to.
+    pub module: String,
+    /// The name of the call.
+    pub function: String,
+    /// The argument names and values.
+    pub arguments: Vec<(String, Value)>,
+}
+
+/// A primitive value.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Primitive {
+    /// A `bool`.
+    Bool(bool),
+    /// A `u8`.
+    U8(u8),
+    /// A `u16`.
+    U16(u16),
+    /// A `u32`.
+    U32(u32),
+    /// A `u64`.
+    U64(u64),
+    /// A `u128`.
+    U128(u128),
+    /// An `i8`.
+    I8(i8),
+    /// An `i16`.
+    I16(i16),
+    /// An `i32`.
+    I32(i32),
+    /// An `i64`.
+    I64(i64),
+    /// An `i128`.
+    I128(i128),
+    /// A string.
+    Str(String),
+    /// Raw bytes, also used for types whose structure is unknown.
+    Bytes(Vec<u8>),
+}
+
+macro_rules! primitives {
+    ($($ty:ident => $variant:ident,)*) => {
+        impl Primitive {
+            /// Decodes a primitive by its type name.
+            ///
+            /// Returns `None` if `name` is not a known primitive.
+            pub(crate) fn decode_named(
+                name: &str,
+                input: &mut &[u8],
+            ) -> Option<Result<Self, CodecError>> {
+                match name {
+                    $(stringify!($ty) => Some(<$ty as Decode>::decode(input).map(Self::$variant)),)*
+                    _ => None,
+                }
+            }
+
+            /// Decodes a primitive of type `T`.
+            ///
+            /// Returns `None` if `T` is not a known primitive.
+            pub(crate) fn decode_typed<T: 'static>(
+                input: &mut &[u8],
+            ) -> Option<Result<Self, CodecError>> {
+                let id = TypeId::of::<T>();
+                $(
+                    if id == TypeId::of::<$ty>() {
+                        return Some(<$ty as Decode>::decode(input).map(Self::$variant))
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+primitives! {
+    bool => Bool,
+    u8 => U8,
+    u16 => U16,
+    u32 => U32,
+    u64 => U64,
+    u128 => U128,
+    i8 => I8,
+    i16 => I16,
+    i32 => I32,
+    i64 => I64,
+    i128 => I128,
+    String => Str,
+}
+
+macro_rules! compact_primitives {
+    ($($ty:ident => |$v:ident| $into:expr,)*) => {
+        impl Primitive {
+            /// Decodes a `Compact<_>` by the name of its inner type.
+            ///
+            /// Returns `None` if `name` is not a known compact primitive.
+            pub(crate) fn decode_compact_named(
+                name: &str,
+                input: &mut &[u8],
+            ) -> Option<Result<Self, CodecError>> {
+                match name {
+                    $(stringify!($ty) => Some(<Compact<$ty>>::decode(input).map(|Compact($v)| $into)),)*
+                    _ => None,
+                }
+            }
+
+            /// Decodes a `Compact<T>`.
+            ///
+            /// Returns `None` if `T` is not a known compact primitive.
+            pub(crate) fn decode_compact_typed<T: 'static>(
+                input: &mut &[u8],
+            ) -> Option<Result<Self, CodecError>> {
+                let id = TypeId::of::<T>();
+                $(
+                    if id == TypeId::of::<$ty>() {
+                        return Some(<Compact<$ty>>::decode(input).map(|Compact($v)| $into))
+                    }
+                )*
+                None
+            }
+        }
+    };
+}
+
+// per-things are compact encoded as their parts
+compact_primitives! {
+    u8 => |v| Self::U8(v),
+    u16 => |v| Self::U16(v),
+    u32 => |v| Self::U32(v),
+    u64 => |v| Self::U64(v),
+    u128 => |v| Self::U128(v),
+    Percent => |v| Self::U8(v.deconstruct()),
+    Permill => |v| Self::U32(v.deconstruct()),
+    Perbill => |v| Self::U32(v.deconstruct()),
+    Perquintill => |v| Self::U64(v.deconstruct()),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Composite(values) => {
+                write!(f, "(")?;
+                write_list(f, values)?;
+                write!(f, ")")
+            }
+            Self::Variant(name, values) => {
+                write!(f, "{}", name)?;
+                if !values.is_empty() {
+                    write!(f, "(")?;
+                    write_list(f, values)?;
+                    write!(f, ")")?;
+                }
+                Ok(())
+            }
+            Self::Sequence(values) => {
+                write!(f, "[")?;
+                write_list(f, values)?;
+                write!(f, "]")
+            }
+            Self::Primitive(primitive) => write!(f, "{}", primitive),
+        }
+    }
+}
+
+impl fmt::Display for Primitive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bool(v) => write!(f, "{}", v),
+            Self::U8(v) => write!(f, "{}", v),
+            Self::U16(v) => write!(f, "{}", v),
+            Self::U32(v) => write!(f, "{}", v),
+            Self::U64(v) => write!(f, "{}", v),
+            Self::U128(v) => write!(f, "{}", v),
+            Self::I8(v) => write!(f, "{}", v),
+            Self::I16(v) => write!(f, "{}", v),
+            Self::I32(v) => write!(f, "{}", v),
+            Self::I64(v) => write!(f, "{}", v),
+            Self::I128(v) => write!(f, "{}", v),
+            Self::Str(v) => write!(f, "{:?}", v),
+            Self::Bytes(v) => write!(f, "0x{}", hex::encode(v)),
+        }
+    }
+}
+
+fn write_list(f: &mut fmt::Formatter<'_>, values: &[Value]) -> fmt::Result {
+    for (i, value) in values.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        write!(f, "{}", value)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codec::Encode;
+
+    #[test]
+    fn test_decode_primitives() {
+        let input = 42u32.encode();
+        assert_eq!(
+            Primitive::decode_named("u32", &mut &input[..])
+                .unwrap()
+                .unwrap(),
+            Primitive::U32(42)
+        );
+        assert_eq!(
+            Primitive::decode_typed::<u32>(&mut &input[..])
+                .unwrap()
+                .unwrap(),
+            Primitive::U32(42)
+        );
+        assert!(Primitive::decode_named("Balance", &mut &input[..]).is_none());
+        assert!(Primitive::decode_typed::<[u8; 4]>(&mut &input[..]).is_none());
+    }
+
+    #[test]
+    fn test_decode_compact_primitives() {
+        let input = Compact(Perbill::from_percent(50)).encode();
+        assert_eq!(
+            Primitive::decode_compact_named("Perbill", &mut &input[..])
+                .unwrap()
+                .unwrap(),
+            Primitive::U32(500_000_000)
+        );
+        let input = Compact(u64::MAX).encode();
+        assert_eq!(
+            Primitive::decode_compact_typed::<u64>(&mut &input[..])
+                .unwrap()
+                .unwrap(),
+            Primitive::U64(u64::MAX)
+        );
+        assert!(Primitive::decode_compact_named("bool", &mut &input[..]).is_none());
+        assert!(Primitive::decode_compact_typed::<String>(&mut &input[..]).is_none());
+    }
+
+    #[test]
+    fn test_display() {
+        let value = Value::Composite(vec![
+            Value::Primitive(Primitive::Bytes(vec![0xde, 0xad])),
+            Value::Variant(
+                "Some".into(),
+                vec![Value::Sequence(vec![
+                    Value::Primitive(Primitive::U8(1)),
+                    Value::Primitive(Primitive::Str("two".into())),
+                ])],
+            ),
+            Value::Variant("None".into(), vec![]),
+        ]);
+        assert_eq!(value.to_string(), r#"(0xdead, Some([1, "two"]), None)"#);
+    }
+}